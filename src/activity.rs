@@ -0,0 +1,203 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{StreamExt, stream};
+use octocrab::Octocrab;
+use tokio::sync::mpsc;
+
+use crate::Error;
+use crate::cache::Cache;
+use crate::forge::github::{self, Activity};
+use crate::forge::{Forge, RepoId};
+use crate::stats::{Contributors, Releases, Repo};
+
+/// How many times to ask again while GitHub computes the statistics.
+const RETRIES: u32 = 5;
+
+/// Wait before the first retry, doubled after each one.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Stats that arrive after the table is up.
+pub enum Update {
+    /// Commits per week, oldest first
+    Commits(RepoId, Vec<u32>),
+    Contributors(RepoId, Contributors),
+    Releases(RepoId, Releases),
+}
+
+/// GitHub repos still missing commit activity, contributors or releases.
+/// GraphQL brings the releases along, REST leaves them to this.
+#[derive(Default)]
+pub struct Pending {
+    pub activity: Vec<RepoId>,
+    pub contributors: Vec<RepoId>,
+    pub releases: Vec<RepoId>,
+}
+
+impl Pending {
+    pub fn of(repos: &[Repo]) -> Self {
+        let github = || repos.iter().filter(|r| r.id.forge == Forge::GitHub);
+        Self {
+            activity: github()
+                .filter(|r| r.commit_activity.is_none())
+                .map(|r| r.id.clone())
+                .collect(),
+            contributors: github()
+                .filter(|r| r.contributors.is_none())
+                .map(|r| r.id.clone())
+                .collect(),
+            releases: github()
+                .filter(|r| r.releases.is_none())
+                .map(|r| r.id.clone())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.activity.is_empty() && self.contributors.is_empty() && self.releases.is_empty()
+    }
+}
+
+/// Why a pass ended early.
+enum Stop {
+    RateLimited,
+    /// Nobody is listening anymore
+    Closed,
+}
+
+/// Fetch what `pending` lacks, sending each repo's stats as soon as GitHub has them.
+/// Asking for commit activity first gets GitHub computing it while the contributors come in,
+/// then repos it was still computing are asked again.
+/// Meant to run in the background, so a rate limit just stops it and the rest stay unknown.
+pub async fn fetch(
+    oct: Octocrab,
+    pending: Pending,
+    jobs: usize,
+    cache: Option<Arc<Cache>>,
+    updates: mpsc::Sender<Update>,
+) {
+    let run = async {
+        let mut computing = activity(&oct, pending.activity, jobs, &cache, &updates).await?;
+        releases(&oct, pending.releases, jobs, &cache, &updates).await?;
+        contributors(&oct, pending.contributors, jobs, &cache, &updates).await?;
+        let mut delay = RETRY_DELAY;
+        for _ in 0..RETRIES {
+            if computing.is_empty() {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+            computing = activity(&oct, computing, jobs, &cache, &updates).await?;
+        }
+        Ok::<_, Stop>(())
+    };
+    let _ = run.await;
+    if let Some(cache) = &cache {
+        let _ = cache.save();
+    }
+}
+
+/// One pass over the statistics endpoint, returning the repos GitHub is still computing.
+async fn activity(
+    oct: &Octocrab,
+    ids: Vec<RepoId>,
+    jobs: usize,
+    cache: &Option<Arc<Cache>>,
+    updates: &mpsc::Sender<Update>,
+) -> Result<Vec<RepoId>, Stop> {
+    let mut computing = Vec::new();
+    let mut requests = stream::iter(ids)
+        .map(|id| async move {
+            let activity = github::commit_activity(oct, &id).await;
+            (id, activity)
+        })
+        .buffer_unordered(jobs.max(1));
+    while let Some((id, activity)) = requests.next().await {
+        match activity {
+            Ok(Activity::Weeks(weeks)) => {
+                if let Some(cache) = cache {
+                    cache.update(&id, |repo| repo.commit_activity = Some(weeks.clone()));
+                }
+                send(updates, Update::Commits(id, weeks)).await?;
+            }
+            Ok(Activity::Computing) => computing.push(id),
+            Err(Error::RateLimit { .. }) => return Err(Stop::RateLimited),
+            Err(_) => {}
+        }
+    }
+    Ok(computing)
+}
+
+async fn contributors(
+    oct: &Octocrab,
+    ids: Vec<RepoId>,
+    jobs: usize,
+    cache: &Option<Arc<Cache>>,
+    updates: &mpsc::Sender<Update>,
+) -> Result<(), Stop> {
+    let mut requests = stream::iter(ids)
+        .map(|id| async move {
+            let contributors = github::contributors(oct, &id).await;
+            (id, contributors)
+        })
+        .buffer_unordered(jobs.max(1));
+    while let Some((id, contributors)) = requests.next().await {
+        match contributors {
+            Ok(contributors) => {
+                if let Some(cache) = cache {
+                    cache.update(&id, |repo| repo.contributors = Some(contributors.clone()));
+                }
+                send(updates, Update::Contributors(id, contributors)).await?;
+            }
+            Err(Error::RateLimit { .. }) => return Err(Stop::RateLimited),
+            Err(_) => {}
+        }
+    }
+    Ok(())
+}
+
+async fn releases(
+    oct: &Octocrab,
+    ids: Vec<RepoId>,
+    jobs: usize,
+    cache: &Option<Arc<Cache>>,
+    updates: &mpsc::Sender<Update>,
+) -> Result<(), Stop> {
+    let mut requests = stream::iter(ids)
+        .map(|id| async move {
+            let releases = github::releases(oct, &id).await;
+            (id, releases)
+        })
+        .buffer_unordered(jobs.max(1));
+    while let Some((id, releases)) = requests.next().await {
+        match releases {
+            Ok(releases) => {
+                if let Some(cache) = cache {
+                    cache.update(&id, |repo| repo.releases = Some(releases.clone()));
+                }
+                send(updates, Update::Releases(id, releases)).await?;
+            }
+            Err(Error::RateLimit { .. }) => return Err(Stop::RateLimited),
+            Err(_) => {}
+        }
+    }
+    Ok(())
+}
+
+async fn send(updates: &mpsc::Sender<Update>, update: Update) -> Result<(), Stop> {
+    updates.send(update).await.map_err(|_| Stop::Closed)
+}
+
+/// Store an update on the repos it belongs to.
+pub fn apply(repos: &mut [Repo], update: Update) {
+    let key = match &update {
+        Update::Commits(id, _) | Update::Contributors(id, _) | Update::Releases(id, _) => id.key(),
+    };
+    for repo in repos.iter_mut().filter(|r| r.id.key() == key) {
+        match &update {
+            Update::Commits(_, weeks) => repo.commit_activity = Some(weeks.clone()),
+            Update::Contributors(_, contributors) => repo.contributors = Some(contributors.clone()),
+            Update::Releases(_, releases) => repo.releases = Some(releases.clone()),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+use crate::forge::RepoId;
+use crate::stats::Repo;
+
+/// A cached `Repo` along with what is needed to revalidate it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub repo: Repo,
+    pub etag: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Persistent response cache, keyed by `host/owner/name`.
+pub struct Cache {
+    path: PathBuf,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl Cache {
+    /// Load the cache from the XDG cache dir.
+    /// A missing or unreadable cache file starts an empty cache.
+    pub fn load(ttl: Duration) -> Self {
+        let path = dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("repotablo")
+            .join("repos.json");
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            ttl,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn get(&self, id: &RepoId) -> Option<Entry> {
+        self.entries.lock().unwrap().get(&id.key()).cloned()
+    }
+
+    /// Entries younger than the TTL are served without touching the network.
+    pub fn fresh(&self, id: &RepoId) -> Option<Repo> {
+        self.get(id)
+            .filter(|entry| Utc::now() - entry.fetched_at < self.ttl)
+            .map(|entry| entry.repo)
+    }
+
+    pub fn insert(&self, id: &RepoId, repo: &Repo, etag: Option<String>) {
+        let entry = Entry {
+            repo: repo.clone(),
+            etag,
+            fetched_at: Utc::now(),
+        };
+        self.entries.lock().unwrap().insert(id.key(), entry);
+    }
+
+    /// Store a repo fetched without an ETag, from GraphQL or a search.
+    /// The ETag of an earlier REST fetch is kept, a later REST run can still revalidate with it.
+    pub fn refresh(&self, id: &RepoId, repo: &Repo) {
+        let mut entries = self.entries.lock().unwrap();
+        let etag = entries.remove(&id.key()).and_then(|entry| entry.etag);
+        let entry = Entry {
+            repo: repo.clone(),
+            etag,
+            fetched_at: Utc::now(),
+        };
+        entries.insert(id.key(), entry);
+    }
+
+    /// Change a cached repo in place, keeping its age.
+    pub fn update(&self, id: &RepoId, change: impl FnOnce(&mut Repo)) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&id.key()) {
+            change(&mut entry.repo);
+        }
+    }
+
+    /// Mark an entry as fresh again after the server answered `304 Not Modified`.
+    /// The etag doesn't cover releases, commit activity or contributors, so those are
+    /// dropped to be fetched again.
+    pub fn touch(&self, id: &RepoId) -> Option<Repo> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(&id.key())?;
+        entry.fetched_at = Utc::now();
+        entry.repo.releases = None;
+        entry.repo.commit_activity = None;
+        entry.repo.contributors = None;
+        Some(entry.repo.clone())
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string(&*self.entries.lock().unwrap())?;
+        std::fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+/// Parse a duration such as `90s`, `30m`, `12h` or `7d`.
+pub fn parse_ttl(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: i64 = value
+        .parse()
+        .map_err(|_| format!("invalid duration `{s}`"))?;
+    match unit {
+        "s" => Ok(Duration::seconds(value)),
+        "m" => Ok(Duration::minutes(value)),
+        "h" | "" => Ok(Duration::hours(value)),
+        "d" => Ok(Duration::days(value)),
+        _ => Err(format!("unknown unit `{unit}`, use s, m, h or d")),
+    }
+}
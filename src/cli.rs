@@ -1,5 +1,11 @@
+use chrono::Duration;
 use clap::Parser;
 
+use crate::cache::parse_ttl;
+use crate::output::Format;
+use crate::stats::Column;
+use crate::ui::SortBy;
+
 #[derive(Parser)]
 #[command(
     name = "Repotablo",
@@ -9,14 +15,74 @@ use clap::Parser;
 )]
 #[derive(Debug)]
 pub struct Opts {
-    /// URL or local file path (opens $EDITOR if not provided)
-    pub input: Option<String>,
+    /// URLs, local file paths, `-` for stdin, `org:name`, `user:name` and `stars:name`
+    /// for a GitHub account's repos, or `search:"query"` for a GitHub search,
+    /// merged together (reads piped stdin, or opens $VISUAL/$EDITOR, if not provided)
+    pub input: Vec<String>,
 
     /// Skip repos with fewer than n stars
     #[arg(long)]
     pub min_stars: Option<u32>,
 
+    /// Print the ranking to stdout in this format instead of starting the UI
+    #[arg(long)]
+    pub format: Option<Format>,
+
+    /// Sort repos by this column
+    #[arg(long)]
+    pub sort: Option<SortBy>,
+
+    /// Extra columns to show, comma separated
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<Column>,
+
+    /// Leave forks out of the results; `org:`, `user:`, `stars:` and `search:` skip them before fetching
+    #[arg(long)]
+    pub exclude_forks: bool,
+
+    /// Leave archived repos out of the results; `org:`, `user:`, `stars:` and `search:` skip them before fetching
+    #[arg(long)]
+    pub exclude_archived: bool,
+
+    /// Stop `search:` inputs after this many results, GitHub serves 1000 at most
+    #[arg(long, default_value_t = 100)]
+    pub limit: usize,
+
+    /// Follow links to other awesome lists this many levels deep
+    #[arg(long, default_value_t = 0)]
+    pub depth: usize,
+
+    /// Number of repos to fetch concurrently
+    #[arg(short, long, default_value_t = 8)]
+    pub jobs: usize,
+
+    /// Serve cached stats younger than this without asking GitHub (e.g. 30m, 12h, 7d).
+    /// Older ones are revalidated with `If-None-Match` without a GitHub token,
+    /// with one they are refetched in full, as GraphQL has no ETags
+    #[arg(long, default_value = "1h", value_parser = parse_ttl)]
+    pub cache_ttl: Duration,
+
+    /// Skip the on-disk cache entirely
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Fail right away when rate limited instead of waiting for the reset
+    #[arg(long)]
+    pub no_wait: bool,
+
     /// GitHub token to avoid rate limiting
     #[clap(long, env)]
     pub github_token: Option<String>,
+
+    /// GitLab token for private projects and higher rate limits
+    #[clap(long, env)]
+    pub gitlab_token: Option<String>,
+
+    /// Self-hosted GitLab instance to send the GitLab token to, besides gitlab.com
+    #[arg(long, value_delimiter = ',')]
+    pub gitlab_host: Vec<String>,
+
+    /// sourcehut token, its API doesn't allow anonymous access
+    #[clap(long, env)]
+    pub srht_token: Option<String>,
 }
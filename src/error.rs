@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use miette::Diagnostic;
 use thiserror::Error;
 
@@ -21,7 +22,10 @@ pub enum Error {
             "https://docs.github.com/en/authentication/keeping-your-account-and-data-secure/creating-a-personal-access-token"
         )
     )]
-    RateLimit,
+    RateLimit {
+        /// When GitHub allows requests again, if it told us
+        reset: Option<DateTime<Utc>>,
+    },
 
     #[error("GitHub error: {0}")]
     GitHub(#[from] octocrab::Error),
@@ -50,3 +54,9 @@ impl std::convert::From<reqwest::Error> for Error {
         Error::Internal(err.to_string())
     }
 }
+
+impl std::convert::From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Internal(err.to_string())
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::Error;
+use crate::forge::{Clients, Forge, RepoId};
+use crate::stats::{Releases, Repo};
+
+/// Gitea, Forgejo and Codeberg answer with the same shape.
+#[derive(Deserialize)]
+struct GiteaRepo {
+    full_name: String,
+    description: Option<String>,
+    website: Option<String>,
+    stars_count: u32,
+    forks_count: u32,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    #[serde(default)]
+    topics: Vec<String>,
+    /// Only sent by recent versions
+    #[serde(default)]
+    licenses: Vec<String>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    fork: bool,
+    parent: Option<Box<GiteaRepo>>,
+    #[serde(default)]
+    template: bool,
+    #[serde(default)]
+    private: bool,
+    #[serde(default)]
+    internal: bool,
+    #[serde(default)]
+    open_issues_count: u32,
+    #[serde(default)]
+    watchers_count: u32,
+    language: Option<String>,
+    /// In kilobytes
+    #[serde(default)]
+    size: u32,
+    default_branch: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    draft: bool,
+}
+
+pub async fn fetch(clients: &Clients, id: &RepoId) -> Result<Option<Repo>, Error> {
+    let url = format!("https://{}/api/v1/repos/{}/{}", id.host, id.owner, id.name);
+    let response = clients.http.get(url).send().await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let repo: GiteaRepo = response.error_for_status()?.json().await?;
+    let releases = releases(clients, id).await;
+
+    let (owner, name) = repo
+        .full_name
+        .split_once('/')
+        .unwrap_or((&id.owner, &id.name));
+    Ok(Some(Repo {
+        id: RepoId {
+            forge: Forge::Gitea,
+            host: id.host.clone(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+        },
+        stars: repo.stars_count,
+        forks: repo.forks_count,
+        license: repo
+            .licenses
+            .into_iter()
+            .next()
+            .map(|l| l.to_lowercase())
+            .unwrap_or_else(|| "None".to_string()),
+        created_at: repo.created_at,
+        pushed_at: repo.updated_at,
+        description: repo.description.filter(|d| !d.is_empty()),
+        topics: repo.topics,
+        homepage: repo.website.filter(|w| !w.is_empty()),
+        archived: repo.archived,
+        fork: repo.fork,
+        parent: repo.parent.map(|p| p.full_name),
+        is_template: repo.template,
+        visibility: Some(
+            match (repo.private, repo.internal) {
+                (true, _) => "private",
+                (_, true) => "internal",
+                _ => "public",
+            }
+            .to_string(),
+        ),
+        open_issues: repo.open_issues_count,
+        watchers: repo.watchers_count,
+        language: repo.language.filter(|l| !l.is_empty()),
+        size: repo.size,
+        default_branch: repo.default_branch,
+        releases,
+        ..Default::default()
+    }))
+}
+
+/// Releases are optional extras, a failure leaves them unknown rather than failing the repo.
+async fn releases(clients: &Clients, id: &RepoId) -> Option<Releases> {
+    let url = format!(
+        "https://{}/api/v1/repos/{}/{}/releases?limit=50",
+        id.host, id.owner, id.name
+    );
+    let response = clients.http.get(url).send().await.ok()?;
+    let releases: Vec<Release> = response.error_for_status().ok()?.json().await.ok()?;
+    Some(Releases::new(
+        releases
+            .into_iter()
+            .filter(|r| !r.draft)
+            .filter_map(|r| r.published_at),
+    ))
+}
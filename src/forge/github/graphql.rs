@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use octocrab::Octocrab;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::forge::{Forge, RepoId};
+use crate::stats::{Lookup, ProblemKind, Releases, Repo};
+use crate::{Error, throttle};
+
+/// How many repositories are requested in a single query.
+/// Keeps each query well below GitHub's node and complexity limits.
+pub const BATCH_SIZE: usize = 50;
+
+const REPO_FIELDS: &str = r#"
+fragment RepoFields on Repository {
+  nameWithOwner
+  stargazerCount
+  forkCount
+  licenseInfo { key }
+  createdAt
+  pushedAt
+  description
+  homepageUrl
+  repositoryTopics(first: 20) { nodes { topic { name } } }
+  isArchived
+  isFork
+  parent { nameWithOwner }
+  isTemplate
+  isDisabled
+  visibility
+  issues(states: OPEN) { totalCount }
+  pullRequests(states: OPEN) { totalCount }
+  watchers { totalCount }
+  primaryLanguage { name }
+  diskUsage
+  defaultBranchRef { name }
+  releases(first: 100, orderBy: { field: CREATED_AT, direction: DESC }) { nodes { publishedAt } }
+}"#;
+
+#[derive(Deserialize)]
+struct Response {
+    data: Option<HashMap<String, Option<Node>>>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    message: String,
+    /// The alias the error belongs to, if any
+    #[serde(default)]
+    path: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Node {
+    name_with_owner: String,
+    stargazer_count: u32,
+    fork_count: u32,
+    license_info: Option<License>,
+    created_at: DateTime<Utc>,
+    pushed_at: Option<DateTime<Utc>>,
+    description: Option<String>,
+    homepage_url: Option<String>,
+    repository_topics: Topics,
+    is_archived: bool,
+    is_fork: bool,
+    parent: Option<Parent>,
+    is_template: bool,
+    is_disabled: bool,
+    /// `PUBLIC`, `PRIVATE` or `INTERNAL`
+    visibility: Option<String>,
+    issues: Count,
+    pull_requests: Count,
+    watchers: Count,
+    primary_language: Option<Name>,
+    /// In kilobytes
+    disk_usage: Option<u32>,
+    default_branch_ref: Option<Name>,
+    releases: ReleaseNodes,
+}
+
+#[derive(Deserialize)]
+struct ReleaseNodes {
+    nodes: Vec<Release>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Release {
+    /// Drafts aren't published
+    published_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Count {
+    total_count: u32,
+}
+
+#[derive(Deserialize)]
+struct Name {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Parent {
+    name_with_owner: String,
+}
+
+#[derive(Deserialize)]
+struct License {
+    key: String,
+}
+
+#[derive(Deserialize)]
+struct Topics {
+    nodes: Vec<TopicNode>,
+}
+
+#[derive(Deserialize)]
+struct TopicNode {
+    topic: Topic,
+}
+
+#[derive(Deserialize)]
+struct Topic {
+    name: String,
+}
+
+/// Fetch a batch of repositories with a single GraphQL query.
+/// The result keeps the order of `repos`.
+pub async fn fetch(oct: &Octocrab, repos: &[RepoId]) -> Result<Vec<Lookup>, Error> {
+    let response = oct._post("/graphql", Some(&build_query(repos))).await?;
+    let reset = throttle::reset_time(response.headers());
+    let response = octocrab::map_github_error(response)
+        .await
+        .map_err(|e| super::rate_limit_or(e, reset))?;
+    let response: Response = serde_json::from_str(&oct.body_to_string(response).await?)?;
+
+    // Errors tied to an alias only affect that repo
+    let mut failures = HashMap::new();
+    for err in response.errors {
+        match err.path.first().and_then(Value::as_str) {
+            Some(alias) => {
+                let kind = match err.kind.as_deref() {
+                    Some("NOT_FOUND") => ProblemKind::NotFound,
+                    _ => ProblemKind::Failed(err.message),
+                };
+                failures.insert(alias.to_string(), kind);
+            }
+            None if err.kind.as_deref() == Some("RATE_LIMITED") => {
+                let reset = reset.or_else(|| Some(Utc::now() + throttle::SECONDARY_LIMIT_WAIT));
+                return Err(Error::RateLimit { reset });
+            }
+            None => return Err(Error::Internal(err.message)),
+        }
+    }
+
+    let mut data = response.data.unwrap_or_default();
+    let repos = repos
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let alias = format!("r{i}");
+            match data.remove(&alias).flatten() {
+                Some(node) => Ok(node.into_repo(&id.host)),
+                None => Err(failures.remove(&alias).unwrap_or(ProblemKind::NotFound)),
+            }
+        })
+        .collect();
+    Ok(repos)
+}
+
+/// Build one aliased `repository` field per repo, passing owner and name as variables
+/// so no escaping is needed.
+fn build_query(repos: &[RepoId]) -> Value {
+    let mut params = Vec::new();
+    let mut fields = Vec::new();
+    let mut variables = Map::new();
+    for (i, RepoId { owner, name, .. }) in repos.iter().enumerate() {
+        params.push(format!("$o{i}: String!, $n{i}: String!"));
+        fields.push(format!(
+            "r{i}: repository(owner: $o{i}, name: $n{i}) {{ ...RepoFields }}"
+        ));
+        variables.insert(format!("o{i}"), json!(owner));
+        variables.insert(format!("n{i}"), json!(name));
+    }
+    let query = format!(
+        "query({}) {{\n{}\n}}\n{}",
+        params.join(", "),
+        fields.join("\n"),
+        REPO_FIELDS
+    );
+    json!({ "query": query, "variables": variables })
+}
+
+impl Node {
+    fn into_repo(self, host: &str) -> Repo {
+        let (owner, name) = self.name_with_owner.split_once('/').unwrap_or_default();
+        Repo {
+            id: RepoId {
+                forge: Forge::GitHub,
+                host: host.to_string(),
+                owner: owner.to_string(),
+                name: name.to_string(),
+            },
+            stars: self.stargazer_count,
+            forks: self.fork_count,
+            license: self
+                .license_info
+                .map(|l| l.key)
+                .unwrap_or_else(|| "None".to_string()),
+            created_at: self.created_at,
+            // Empty repositories have never been pushed to
+            pushed_at: self.pushed_at.unwrap_or(self.created_at),
+            description: self.description,
+            topics: self
+                .repository_topics
+                .nodes
+                .into_iter()
+                .map(|n| n.topic.name)
+                .collect(),
+            homepage: self.homepage_url.filter(|h| !h.is_empty()),
+            archived: self.is_archived,
+            fork: self.is_fork,
+            parent: self.parent.map(|p| p.name_with_owner),
+            is_template: self.is_template,
+            disabled: self.is_disabled,
+            visibility: self.visibility.map(|v| v.to_lowercase()),
+            // Like REST, which counts pull requests as issues
+            open_issues: self.issues.total_count + self.pull_requests.total_count,
+            watchers: self.watchers.total_count,
+            language: self.primary_language.map(|l| l.name),
+            size: self.disk_usage.unwrap_or_default(),
+            default_branch: self.default_branch_ref.map(|b| b.name),
+            releases: Some(Releases::new(
+                self.releases
+                    .nodes
+                    .into_iter()
+                    .filter_map(|r| r.published_at),
+            )),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,388 @@
+pub mod graphql;
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+use octocrab::Octocrab;
+use octocrab::models::Repository;
+use serde::Deserialize;
+
+use crate::forge::{Forge, RepoId};
+use crate::stats::{Contributors, Releases, Repo};
+use crate::{Error, throttle};
+
+/// Outcome of a single REST lookup.
+pub enum Fetched {
+    Found {
+        repo: Box<Repo>,
+        etag: Option<String>,
+    },
+    /// The cached copy is still valid
+    NotModified,
+    NotFound,
+}
+
+/// Fetch a repo through REST.
+/// With an `etag`, the request is conditional and may answer `NotModified`,
+/// which doesn't count against the rate limit.
+pub async fn fetch(oct: &Octocrab, id: &RepoId, etag: Option<&str>) -> Result<Fetched, Error> {
+    let mut headers = HeaderMap::new();
+    if let Some(etag) = etag.and_then(|e| HeaderValue::from_str(e).ok()) {
+        headers.insert(header::IF_NONE_MATCH, etag);
+    }
+    let response = oct
+        ._get_with_headers(format!("/repos/{}/{}", id.owner, id.name), Some(headers))
+        .await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    let reset = throttle::reset_time(response.headers());
+    let etag = response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+
+    let response = match octocrab::map_github_error(response).await {
+        Ok(response) => response,
+        Err(octocrab::Error::GitHub { source, .. }) if source.status_code == 404 => {
+            return Ok(Fetched::NotFound);
+        }
+        Err(e) => return Err(rate_limit_or(e, reset)),
+    };
+    let info: Repository = serde_json::from_str(&oct.body_to_string(response).await?)?;
+
+    let repo = into_repo(info, id)
+        .ok_or_else(|| Error::Internal(format!("{id} came without a creation date")))?;
+    Ok(Fetched::Found {
+        repo: Box::new(repo),
+        etag,
+    })
+}
+
+#[derive(Deserialize)]
+struct Release {
+    published_at: Option<DateTime<Utc>>,
+}
+
+/// The newest hundred releases, enough to count a year's worth for most repos.
+/// A request of its own over REST, so it comes in the background with the activity.
+pub async fn releases(oct: &Octocrab, id: &RepoId) -> Result<Releases, Error> {
+    let response = oct
+        ._get(format!(
+            "/repos/{}/{}/releases?per_page=100",
+            id.owner, id.name
+        ))
+        .await?;
+    let reset = throttle::reset_time(response.headers());
+    let response = octocrab::map_github_error(response)
+        .await
+        .map_err(|e| rate_limit_or(e, reset))?;
+    let releases: Vec<Release> = serde_json::from_str(&oct.body_to_string(response).await?)?;
+    Ok(Releases::new(
+        releases.into_iter().filter_map(|r| r.published_at),
+    ))
+}
+
+/// Answer of the statistics endpoint.
+pub enum Activity {
+    /// Commits per week over the last year, oldest first
+    Weeks(Vec<u32>),
+    /// GitHub is computing the statistics, ask again in a moment
+    Computing,
+}
+
+#[derive(Deserialize)]
+struct Week {
+    total: u32,
+}
+
+/// Weekly commit counts, from the statistics GitHub computes on demand.
+pub async fn commit_activity(oct: &Octocrab, id: &RepoId) -> Result<Activity, Error> {
+    let response = oct
+        ._get(format!(
+            "/repos/{}/{}/stats/commit_activity",
+            id.owner, id.name
+        ))
+        .await?;
+    match response.status() {
+        StatusCode::ACCEPTED => return Ok(Activity::Computing),
+        // Empty repos have nothing to count
+        StatusCode::NO_CONTENT => return Ok(Activity::Weeks(Vec::new())),
+        _ => {}
+    }
+    let reset = throttle::reset_time(response.headers());
+    let response = octocrab::map_github_error(response)
+        .await
+        .map_err(|e| rate_limit_or(e, reset))?;
+    let weeks: Vec<Week> = serde_json::from_str(&oct.body_to_string(response).await?)?;
+    Ok(Activity::Weeks(
+        weeks.into_iter().map(|w| w.total).collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+struct Contributor {
+    login: String,
+    contributions: u32,
+}
+
+/// The top hundred contributors by commits, and how many there are in all.
+pub async fn contributors(oct: &Octocrab, id: &RepoId) -> Result<Contributors, Error> {
+    let url = format!("/repos/{}/{}/contributors", id.owner, id.name);
+    let response = oct._get(format!("{url}?per_page=100")).await?;
+    // Empty repos have nobody to count
+    if response.status() == StatusCode::NO_CONTENT {
+        return Ok(Contributors::default());
+    }
+    let reset = throttle::reset_time(response.headers());
+    let more = last_page(response.headers()).is_some();
+    let response = octocrab::map_github_error(response)
+        .await
+        .map_err(|e| rate_limit_or(e, reset))?;
+    let top: Vec<Contributor> = serde_json::from_str(&oct.body_to_string(response).await?)?;
+
+    // With a single contributor per page, the last page number is the count.
+    // The top hundred are worth keeping even if GitHub won't tell, the count is just unknown then.
+    let count = match more {
+        true => {
+            let response = oct._get(format!("{url}?per_page=1")).await?;
+            match response.status().is_success() {
+                true => last_page(response.headers()),
+                false => None,
+            }
+        }
+        false => Some(top.len() as u32),
+    };
+    let commits = top.into_iter().map(|c| (c.login, c.contributions));
+    Ok(Contributors::new(commits.collect(), count))
+}
+
+/// GitHub answers 403 for rate limits, but also for repos the token may not see,
+/// e.g. in SAML-protected orgs, or with too much history to count contributors.
+/// Only a rate limit pauses the run, anything else is a problem with that one repo.
+/// Secondary limits may not say when to retry, GitHub documents waiting a minute then.
+fn rate_limit_or(err: octocrab::Error, reset: Option<DateTime<Utc>>) -> Error {
+    let octocrab::Error::GitHub { source, .. } = &err else {
+        return Error::GitHub(err);
+    };
+    let limited = match source.status_code {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => {
+            reset.is_some() || source.message.to_lowercase().contains("rate limit")
+        }
+        _ => false,
+    };
+    match limited {
+        true => Error::RateLimit {
+            reset: reset.or_else(|| Some(Utc::now() + throttle::SECONDARY_LIMIT_WAIT)),
+        },
+        false => Error::GitHub(err),
+    }
+}
+
+/// The page number of the `rel="last"` link, absent when everything fit in one page.
+fn last_page(headers: &HeaderMap) -> Option<u32> {
+    let links = headers.get(header::LINK)?.to_str().ok()?;
+    let last = links
+        .split(',')
+        .find(|link| link.contains("rel=\"last\""))?;
+    let url = last.split(['<', '>']).nth(1)?;
+    url.split(['?', '&'])
+        .find_map(|param| param.strip_prefix("page="))?
+        .parse()
+        .ok()
+}
+
+/// The README through the contents API, for reading a linked awesome list.
+pub async fn readme(oct: &Octocrab, id: &RepoId) -> Result<String, Error> {
+    let content = oct
+        .repos(&id.owner, &id.name)
+        .get_readme()
+        .send()
+        .await
+        .map_err(|e| rate_limit_or(e, None))?;
+    content
+        .decoded_content()
+        .ok_or_else(|| Error::NotFound(format!("{id} has an empty README")))
+}
+
+/// A list of repos GitHub can enumerate for us, instead of reading them from a document.
+#[derive(Debug, Clone)]
+pub enum Listing {
+    /// `org:tokio-rs`
+    Org(String),
+    /// `user:azzamsa`, repos the user owns
+    User(String),
+    /// `stars:azzamsa`, repos the user starred
+    Starred(String),
+}
+
+impl Listing {
+    pub fn parse(input: &str) -> Option<Self> {
+        let (kind, name) = input.split_once(':')?;
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return None;
+        }
+        match kind {
+            "org" => Some(Self::Org(name)),
+            "user" => Some(Self::User(name)),
+            "stars" => Some(Self::Starred(name)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Org(name) => write!(f, "org:{name}"),
+            Self::User(name) => write!(f, "user:{name}"),
+            Self::Starred(name) => write!(f, "stars:{name}"),
+        }
+    }
+}
+
+/// Which repos to leave out of a listing, and how far to page through searches.
+#[derive(Debug, Clone, Copy)]
+pub struct ListOptions {
+    pub exclude_forks: bool,
+    pub exclude_archived: bool,
+    pub search_limit: usize,
+}
+
+impl ListOptions {
+    fn keep(&self, repo: &Repository) -> bool {
+        let fork = self.exclude_forks && repo.fork == Some(true);
+        let archived = self.exclude_archived && repo.archived == Some(true);
+        !fork && !archived
+    }
+}
+
+/// Walk every page of a listing.
+pub async fn list(
+    oct: &Octocrab,
+    listing: &Listing,
+    opts: ListOptions,
+) -> Result<Vec<RepoId>, Error> {
+    let first = match listing {
+        Listing::Org(org) => oct.orgs(org).list_repos().per_page(100).send().await?,
+        Listing::User(user) => oct.users(user).repos().per_page(100).send().await?,
+        Listing::Starred(user) => {
+            oct.get(format!("/users/{user}/starred"), Some(&[("per_page", 100)]))
+                .await?
+        }
+    };
+    let repos: Vec<Repository> = oct.all_pages(first).await?;
+    let ids = repos
+        .iter()
+        .filter(|r| opts.keep(r))
+        .filter_map(model_id)
+        .collect();
+    Ok(ids)
+}
+
+/// Run a repository search, up to `opts.search_limit` results.
+/// Results carry every stat already, so they skip the per-repo fetch.
+pub async fn search(oct: &Octocrab, query: &str, opts: ListOptions) -> Result<Vec<Repo>, Error> {
+    let per_page = opts.search_limit.clamp(1, 100) as u8;
+    let mut page = oct
+        .search()
+        .repositories(query)
+        .per_page(per_page)
+        .send()
+        .await?;
+    let mut found = page.take_items();
+    while found.len() < opts.search_limit
+        && let Some(mut next) = oct.get_page::<Repository>(&page.next).await?
+    {
+        found.extend(next.take_items());
+        page = next;
+    }
+    found.truncate(opts.search_limit);
+
+    let repos = found
+        .into_iter()
+        .filter(|r| opts.keep(r))
+        .filter_map(|r| {
+            let id = model_id(&r)?;
+            into_repo(r, &id)
+        })
+        .collect();
+    Ok(repos)
+}
+
+fn model_id(repo: &Repository) -> Option<RepoId> {
+    let (owner, name) = repo.full_name.as_deref()?.split_once('/')?;
+    Some(RepoId {
+        forge: Forge::GitHub,
+        host: "github.com".into(),
+        owner: owner.into(),
+        name: name.into(),
+    })
+}
+
+/// Shared by the REST lookup and search results.
+/// `None` without a creation date, rather than making the repo up as born in 1970.
+fn into_repo(info: Repository, id: &RepoId) -> Option<Repo> {
+    let stars = info.stargazers_count.unwrap_or(0);
+    let forks = info.forks_count.unwrap_or(0);
+    let license = info
+        .license
+        .as_ref()
+        .map(|l| l.key.clone())
+        .unwrap_or_else(|| "None".to_string());
+    let age = info.created_at?;
+    // Empty repositories have never been pushed to
+    let last_push = info.pushed_at.unwrap_or(age);
+    let description = info.description;
+    let topics = info.topics.unwrap_or_default();
+    let homepage = info.homepage;
+    let parent = info.parent.and_then(|p| p.full_name);
+    let language = info
+        .language
+        .as_ref()
+        .and_then(|l| l.as_str())
+        .map(String::from);
+
+    // Renamed repos redirect, take the name GitHub answered with
+    let (owner, name) = info
+        .full_name
+        .as_deref()
+        .and_then(|full_name| full_name.split_once('/'))
+        .unwrap_or((&id.owner, &id.name));
+    Some(Repo {
+        id: RepoId {
+            forge: Forge::GitHub,
+            host: id.host.clone(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+        },
+        stars,
+        forks,
+        license,
+        created_at: age,
+        pushed_at: last_push,
+        description,
+        topics,
+        homepage,
+        archived: info.archived.unwrap_or_default(),
+        fork: info.fork.unwrap_or_default(),
+        parent,
+        is_template: info.is_template.unwrap_or_default(),
+        disabled: info.disabled.unwrap_or_default(),
+        visibility: info.visibility,
+        open_issues: info.open_issues_count.unwrap_or_default(),
+        watchers: info
+            .subscribers_count
+            .and_then(|n| u32::try_from(n).ok())
+            .unwrap_or_default(),
+        language,
+        size: info.size.unwrap_or_default(),
+        default_branch: info.default_branch,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::Error;
+use crate::forge::{Clients, Forge, RepoId};
+use crate::stats::{Contributors, Releases, Repo};
+
+#[derive(Deserialize)]
+struct Project {
+    path_with_namespace: String,
+    description: Option<String>,
+    star_count: u32,
+    forks_count: u32,
+    created_at: DateTime<Utc>,
+    last_activity_at: DateTime<Utc>,
+    #[serde(default)]
+    topics: Vec<String>,
+    license: Option<License>,
+    #[serde(default)]
+    archived: bool,
+    forked_from_project: Option<ForkedFrom>,
+    visibility: Option<String>,
+    /// Missing when the issue tracker is disabled
+    #[serde(default)]
+    open_issues_count: u32,
+    default_branch: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ForkedFrom {
+    path_with_namespace: String,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    /// Upcoming releases are dated in the future
+    released_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct Contributor {
+    name: String,
+    commits: u32,
+}
+
+#[derive(Deserialize)]
+struct License {
+    key: String,
+}
+
+pub async fn fetch(clients: &Clients, id: &RepoId) -> Result<Option<Repo>, Error> {
+    // The project path is a single, encoded, path segment
+    let path = format!("{}/{}", id.owner, id.name).replace('/', "%2F");
+    let url = format!("https://{}/api/v4/projects/{}?license=true", id.host, path);
+    let response = get(clients, &id.host, url).send().await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let project: Project = response.error_for_status()?.json().await?;
+    let releases = releases(clients, &id.host, &path).await;
+    let contributors = contributors(clients, &id.host, &path).await;
+
+    let (owner, name) = project
+        .path_with_namespace
+        .rsplit_once('/')
+        .unwrap_or((&id.owner, &id.name));
+    Ok(Some(Repo {
+        id: RepoId {
+            forge: Forge::GitLab,
+            host: id.host.clone(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+        },
+        stars: project.star_count,
+        forks: project.forks_count,
+        license: project
+            .license
+            .map(|l| l.key)
+            .unwrap_or_else(|| "None".to_string()),
+        created_at: project.created_at,
+        pushed_at: project.last_activity_at,
+        description: project.description.filter(|d| !d.is_empty()),
+        topics: project.topics,
+        homepage: None,
+        archived: project.archived,
+        fork: project.forked_from_project.is_some(),
+        parent: project.forked_from_project.map(|p| p.path_with_namespace),
+        visibility: project.visibility,
+        open_issues: project.open_issues_count,
+        default_branch: project.default_branch,
+        releases,
+        contributors,
+        ..Default::default()
+    }))
+}
+
+/// Only hosts the token is meant for get it, a link in someone else's list can point anywhere.
+fn get(clients: &Clients, host: &str, url: String) -> reqwest::RequestBuilder {
+    let request = clients.http.get(url);
+    match clients.gitlab_token_for(host) {
+        Some(token) => request.header("PRIVATE-TOKEN", token),
+        None => request,
+    }
+}
+
+/// Releases are optional extras, a failure leaves them unknown rather than failing the project.
+async fn releases(clients: &Clients, host: &str, path: &str) -> Option<Releases> {
+    let url = format!("https://{host}/api/v4/projects/{path}/releases?per_page=100");
+    let response = get(clients, host, url).send().await.ok()?;
+    let response = response.error_for_status().ok()?;
+    let releases: Vec<Release> = response.json().await.ok()?;
+    let now = Utc::now();
+    Some(Releases::new(
+        releases
+            .into_iter()
+            .filter_map(|r| r.released_at)
+            .filter(|date| *date <= now),
+    ))
+}
+
+/// Contributors by commits on the default branch, best effort like the releases.
+async fn contributors(clients: &Clients, host: &str, path: &str) -> Option<Contributors> {
+    let url = format!(
+        "https://{host}/api/v4/projects/{path}/repository/contributors?order_by=commits&sort=desc&per_page=100"
+    );
+    let response = get(clients, host, url).send().await.ok()?;
+    let response = response.error_for_status().ok()?;
+    // Left out for very large projects
+    let total = response
+        .headers()
+        .get("x-total")
+        .and_then(|v| v.to_str().ok()?.parse().ok());
+    let top: Vec<Contributor> = response.json().await.ok()?;
+    // A short page holds them all, a full one only says there are more
+    let count = total.or_else(|| (top.len() < 100).then_some(top.len() as u32));
+    let commits = top.into_iter().map(|c| (c.name, c.commits));
+    Some(Contributors::new(commits.collect(), count))
+}
@@ -0,0 +1,325 @@
+pub mod gitea;
+pub mod github;
+pub mod gitlab;
+pub mod sourcehut;
+
+use std::fmt;
+
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+use crate::stats::Repo;
+
+/// Code hosting platforms repotablo knows how to query.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    #[default]
+    GitHub,
+    GitLab,
+    /// Gitea and its Forgejo fork (Codeberg), they share an API
+    Gitea,
+    SourceHut,
+}
+
+impl Forge {
+    /// Self-hosted instances are recognized by their usual subdomain.
+    pub fn from_host(host: &str) -> Option<Self> {
+        match host {
+            "github.com" => Some(Self::GitHub),
+            "gitlab.com" => Some(Self::GitLab),
+            "codeberg.org" | "gitea.com" => Some(Self::Gitea),
+            "git.sr.ht" => Some(Self::SourceHut),
+            h if h.starts_with("gitlab.") => Some(Self::GitLab),
+            h if h.starts_with("gitea.") || h.starts_with("forgejo.") => Some(Self::Gitea),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Forge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::GitHub => "GitHub",
+            Self::GitLab => "GitLab",
+            Self::Gitea => "Gitea",
+            Self::SourceHut => "sourcehut",
+        };
+        f.write_str(name)
+    }
+}
+
+/// First path segments on github.com that are site pages rather than owners.
+const GITHUB_RESERVED: &[&str] = &[
+    "about",
+    "apps",
+    "collections",
+    "customer-stories",
+    "enterprise",
+    "events",
+    "explore",
+    "features",
+    "issues",
+    "login",
+    "marketplace",
+    "new",
+    "notifications",
+    "orgs",
+    "pricing",
+    "pulls",
+    "resources",
+    "search",
+    "settings",
+    "site",
+    "solutions",
+    "sponsors",
+    "topics",
+    "trending",
+];
+
+/// Project pages GitLab linked to without the `/-/` separator before it added one.
+const GITLAB_PAGES: &[&str] = &[
+    "blob",
+    "blame",
+    "branches",
+    "commit",
+    "commits",
+    "compare",
+    "issues",
+    "merge_requests",
+    "pipelines",
+    "raw",
+    "tags",
+    "tree",
+    "wikis",
+];
+
+/// A repository on some forge.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RepoId {
+    pub forge: Forge,
+    pub host: String,
+    /// GitLab owners may contain nested groups, e.g. `group/subgroup`
+    pub owner: String,
+    pub name: String,
+}
+
+impl RepoId {
+    /// Normalize any way of writing a repo link into an identifier:
+    /// `https://`, `http://`, scheme-less `github.com/o/r`, SSH `git@github.com:o/r.git`,
+    /// `www.` hosts, `.git` suffixes, query strings, fragments and deep links.
+    /// Returns `None` for unknown hosts and paths that don't name a repo.
+    pub fn parse(url: &str) -> Option<Self> {
+        // Trailing punctuation usually belongs to the surrounding prose
+        let url = url.trim().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+        // SSH user, as in `git@github.com:o/r`
+        let rest = match rest.split_once('@') {
+            Some((user, rest)) if !user.contains(['/', ':']) => rest,
+            _ => rest,
+        };
+        let split = rest.find(['/', ':'])?;
+        let (host, path) = (&rest[..split], &rest[split + 1..]);
+        // `ssh://git@host:22/o/r` carries a port before the path, numeric owners don't
+        let path = match path.split_once('/') {
+            Some((port, path))
+                if scheme.eq_ignore_ascii_case("ssh")
+                    && rest[split..].starts_with(':')
+                    && port.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                path
+            }
+            _ => path,
+        };
+        let path = path.split(['?', '#']).next()?;
+
+        let host = host.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+        Self::from_url_parts(host, path)
+    }
+
+    fn from_url_parts(host: &str, path: &str) -> Option<Self> {
+        let forge = Forge::from_host(host)?;
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let (owner, name) = match forge {
+            Forge::GitLab => {
+                // Everything after `/-/` is a page inside the project,
+                // older links go straight to the page, as in `group/project/issues`
+                let end = segments
+                    .iter()
+                    .enumerate()
+                    .position(|(i, s)| *s == "-" || (i >= 2 && GITLAB_PAGES.contains(s)))
+                    .unwrap_or(segments.len());
+                let (name, owner) = segments[..end].split_last()?;
+                if owner.is_empty() {
+                    return None;
+                }
+                (owner.join("/"), *name)
+            }
+            Forge::SourceHut => {
+                let owner = segments.first()?.strip_prefix('~')?;
+                (owner.to_string(), *segments.get(1)?)
+            }
+            Forge::GitHub | Forge::Gitea => (segments.first()?.to_string(), *segments.get(1)?),
+        };
+        if forge == Forge::GitHub && GITHUB_RESERVED.contains(&owner.to_lowercase().as_str()) {
+            return None;
+        }
+        let name = name.strip_suffix(".git").unwrap_or(name);
+        if name.is_empty() {
+            return None;
+        }
+        Some(Self {
+            forge,
+            host: host.to_string(),
+            owner,
+            name: name.to_string(),
+        })
+    }
+
+    /// Forges treat owner and name case-insensitively, so does this key.
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.host, self.owner, self.name).to_lowercase()
+    }
+
+    pub fn url(&self) -> String {
+        match self.forge {
+            Forge::SourceHut => format!("https://{}/~{}/{}", self.host, self.owner, self.name),
+            _ => format!("https://{}/{}/{}", self.host, self.owner, self.name),
+        }
+    }
+}
+
+/// `owner/name` on GitHub, `host/owner/name` everywhere else.
+impl fmt::Display for RepoId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.forge {
+            Forge::GitHub => write!(f, "{}/{}", self.owner, self.name),
+            Forge::SourceHut => write!(f, "{}/~{}/{}", self.host, self.owner, self.name),
+            _ => write!(f, "{}/{}/{}", self.host, self.owner, self.name),
+        }
+    }
+}
+
+/// Authenticated clients for every forge.
+pub struct Clients {
+    pub github: Octocrab,
+    /// Shared by the forges without a dedicated SDK
+    pub http: reqwest::Client,
+    pub gitlab_token: Option<String>,
+    /// Self-hosted GitLab instances trusted with `gitlab_token`, besides gitlab.com
+    pub gitlab_hosts: Vec<String>,
+    pub srht_token: Option<String>,
+}
+
+impl Clients {
+    /// The GitLab token, if `host` is gitlab.com or one the user named.
+    /// Any `gitlab.*` host counts as GitLab, but that alone doesn't earn it the token.
+    pub fn gitlab_token_for(&self, host: &str) -> Option<&str> {
+        let trusted = host == "gitlab.com"
+            || self
+                .gitlab_hosts
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case(host));
+        self.gitlab_token.as_deref().filter(|_| trusted)
+    }
+}
+
+/// Fetch a single repo without any caching or batching.
+/// GitHub has a richer pipeline in `Stats::fetch`, this is what every other forge goes through.
+pub async fn fetch(clients: &Clients, id: &RepoId) -> Result<Option<Repo>, Error> {
+    match id.forge {
+        Forge::GitHub => match github::fetch(&clients.github, id, None).await? {
+            github::Fetched::Found { repo, .. } => Ok(Some(*repo)),
+            github::Fetched::NotModified | github::Fetched::NotFound => Ok(None),
+        },
+        Forge::GitLab => gitlab::fetch(clients, id).await,
+        Forge::Gitea => gitea::fetch(clients, id).await,
+        Forge::SourceHut => sourcehut::fetch(clients, id).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repo_id() {
+        let cases = [
+            ("https://github.com/o/r", Some("o/r")),
+            ("http://github.com/o/r", Some("o/r")),
+            ("github.com/o/r", Some("o/r")),
+            ("https://www.github.com/o/r", Some("o/r")),
+            ("git@github.com:o/r.git", Some("o/r")),
+            ("ssh://git@github.com:22/o/r.git", Some("o/r")),
+            ("git://github.com/o/r", Some("o/r")),
+            ("https://github.com/o/r?tab=readme#usage", Some("o/r")),
+            ("https://github.com/o/r/tree/main/src", Some("o/r")),
+            ("https://github.com/o/r/issues/1", Some("o/r")),
+            ("See https://github.com/o/r.", Some("o/r")),
+            ("https://GitHub.com/O/R", Some("O/R")),
+            ("https://github.com/1234/foo", Some("1234/foo")),
+            (
+                "https://gitlab.com/12345/proj",
+                Some("gitlab.com/12345/proj"),
+            ),
+            ("https://github.com/o", None),
+            ("https://github.com/topics/rust", None),
+            ("https://github.com/solutions/industry/healthcare", None),
+            ("https://example.com/o/r", None),
+            (
+                "https://gitlab.com/group/project",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.com/group/sub/project",
+                Some("gitlab.com/group/sub/project"),
+            ),
+            (
+                "https://gitlab.com/group/project/-/issues/1",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.com/group/project/issues",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.com/group/project/merge_requests/2",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.com/group/project/tree/master/src",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.com/group/project/blob/master/README.md",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.com/group/project/wikis/home",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.com/group/tree",
+                Some("gitlab.com/group/tree"),
+            ),
+            (
+                "git@gitlab.com:group/project.git",
+                Some("gitlab.com/group/project"),
+            ),
+            (
+                "https://gitlab.gnome.org/GNOME/gtk",
+                Some("gitlab.gnome.org/GNOME/gtk"),
+            ),
+            ("https://gitlab.com/group", None),
+            ("https://codeberg.org/o/r", Some("codeberg.org/o/r")),
+            ("https://git.sr.ht/~o/r", Some("git.sr.ht/~o/r")),
+            ("https://git.sr.ht/o/r", None),
+        ];
+        for (url, expected) in cases {
+            let id = RepoId::parse(url).map(|id| id.to_string());
+            assert_eq!(id.as_deref(), expected, "{url}");
+        }
+    }
+}
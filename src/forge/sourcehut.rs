@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::Error;
+use crate::forge::{Clients, Forge, RepoId};
+use crate::stats::Repo;
+
+const QUERY: &str = r#"
+query($owner: String!, $name: String!) {
+  user(username: $owner) {
+    repository(name: $name) { name description created updated visibility }
+  }
+}"#;
+
+#[derive(Deserialize)]
+struct Response {
+    data: Option<Data>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    user: Option<User>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    repository: Option<Repository>,
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    name: String,
+    description: Option<String>,
+    created: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    /// `PUBLIC`, `UNLISTED` or `PRIVATE`
+    visibility: Option<String>,
+}
+
+/// sourcehut has no stars or forks, only the dates and description are filled.
+pub async fn fetch(clients: &Clients, id: &RepoId) -> Result<Option<Repo>, Error> {
+    // The sourcehut API doesn't allow anonymous access
+    let Some(token) = &clients.srht_token else {
+        return Err(Error::InvalidArgument(
+            "sourcehut needs a token, set SRHT_TOKEN".into(),
+        ));
+    };
+    let body = json!({
+        "query": QUERY,
+        "variables": { "owner": id.owner, "name": id.name },
+    });
+    let response = clients
+        .http
+        .post(format!("https://{}/query", id.host))
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    let response: Response = response.json().await?;
+
+    let repository = response
+        .data
+        .and_then(|data| data.user)
+        .and_then(|user| user.repository);
+    let Some(repository) = repository else {
+        return match response.errors.into_iter().next() {
+            Some(err) => Err(Error::Internal(err.message)),
+            None => Ok(None),
+        };
+    };
+    Ok(Some(Repo {
+        id: RepoId {
+            forge: Forge::SourceHut,
+            host: id.host.clone(),
+            owner: id.owner.clone(),
+            name: repository.name,
+        },
+        stars: 0,
+        forks: 0,
+        license: "None".to_string(),
+        created_at: repository.created,
+        pushed_at: repository.updated,
+        description: repository.description.filter(|d| !d.is_empty()),
+        topics: Vec::new(),
+        homepage: None,
+        visibility: repository.visibility.map(|v| v.to_lowercase()),
+        ..Default::default()
+    }))
+}
@@ -0,0 +1,70 @@
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::Error;
+use crate::forge::RepoId;
+use crate::input::{Entry, dedup};
+
+/// The part of `cargo metadata --format-version 1` we care about.
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    repository: Option<String>,
+    /// `None` for workspace members and path dependencies
+    source: Option<String>,
+}
+
+/// Resolve the dependencies of a `Cargo.toml` (or the `Cargo.lock` next to it) through cargo.
+/// `--locked` keeps cargo from rewriting the lock file, and the network is only
+/// used when the dependencies haven't been downloaded yet.
+pub fn from_manifest(path: &Path) -> Result<Vec<Entry>, Error> {
+    let manifest = match path.file_name() {
+        Some(name) if name == "Cargo.lock" => path.with_file_name("Cargo.toml"),
+        _ => path.to_path_buf(),
+    };
+    let metadata = |offline: bool| {
+        let mut cargo = Command::new("cargo");
+        cargo.args(["metadata", "--format-version", "1", "--locked"]);
+        if offline {
+            cargo.arg("--offline");
+        }
+        cargo.arg("--manifest-path").arg(&manifest).output()
+    };
+    let mut output = metadata(true)?;
+    if !output.status.success() {
+        output = metadata(false)?;
+    }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Internal(format!(
+            "cargo metadata failed: {}",
+            stderr.trim()
+        )));
+    }
+    parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Read a saved `cargo metadata` output.
+/// Crates are listed by their repo, several crates from one monorepo share an entry.
+pub fn parse(json: &str) -> Result<Vec<Entry>, Error> {
+    let metadata: Metadata = serde_json::from_str(json)
+        .map_err(|e| Error::InvalidArgument(format!("not a `cargo metadata` output: {e}")))?;
+
+    // Our own crates are what's being audited, not part of the list
+    let dependencies = metadata.packages.into_iter().filter(|p| p.source.is_some());
+    let entries = dependencies.filter_map(|package| {
+        Some(Entry {
+            id: RepoId::parse(package.repository.as_deref()?)?,
+            packages: vec![package.name],
+            ..Default::default()
+        })
+    });
+    Ok(dedup(entries))
+}
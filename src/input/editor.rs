@@ -0,0 +1,82 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::Error;
+use crate::input::{Entry, parse_repos};
+
+const INSTRUCTIONS: &str = "<!--
+Paste repository links below, as plain URLs or a markdown list.
+Markdown headings become categories.
+Comments like this one are ignored. Save and quit to start ranking.
+-->
+";
+
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then the platform's default.
+/// Split with shell rules, so `code --wait` works.
+pub fn command() -> Result<Command, Error> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let words = shlex::split(&editor)
+        .filter(|words| !words.is_empty())
+        .ok_or_else(|| Error::InvalidArgument(format!("can't parse editor command `{editor}`")))?;
+    let mut command = Command::new(&words[0]);
+    command.args(&words[1..]);
+    Ok(command)
+}
+
+/// Let the user write the list, starting from the one they wrote last time.
+pub fn open() -> Result<Vec<Entry>, Error> {
+    let mut tmp = tempfile::Builder::new()
+        .prefix("repotablo-")
+        .suffix(".md")
+        .tempfile()?;
+    let previous = std::fs::read_to_string(last_list()).unwrap_or_default();
+    write!(tmp, "{INSTRUCTIONS}\n{previous}")?;
+    tmp.flush()?;
+
+    let mut command = command()?;
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .arg(tmp.path())
+        .status()
+        .map_err(|e| Error::InvalidArgument(format!("failed to run editor `{program}`: {e}")))?;
+    if !status.success() {
+        return Err(Error::InvalidArgument(format!(
+            "editor `{program}` exited with {status}"
+        )));
+    }
+
+    let content = strip_comments(&std::fs::read_to_string(tmp.path())?);
+    // Losing the list for next time isn't worth failing over
+    let path = last_list();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(path, content.trim().to_string() + "\n");
+    Ok(parse_repos(&content))
+}
+
+/// Blank out `<!-- -->` comments, keeping their lines so line numbers still match the buffer.
+fn strip_comments(content: &str) -> String {
+    COMMENT
+        .replace_all(content, |c: &regex::Captures| {
+            "\n".repeat(c[0].matches('\n').count())
+        })
+        .into_owned()
+}
+
+fn last_list() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("repotablo")
+        .join("last-list.md")
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+use crate::forge::RepoId;
+use crate::input::{Entry, dedup};
+
+/// Deep enough for `~/src/github.com/owner/name` layouts.
+const MAX_DEPTH: usize = 6;
+
+/// Directories that hold dependencies rather than clones.
+const SKIP: [&str; 3] = ["node_modules", "target", "vendor"];
+
+/// Find the git clones under `root` and the repos their remotes point to.
+pub fn from_dir(root: &Path) -> Result<Vec<Entry>, Error> {
+    let mut clones = Vec::new();
+    walk(root, 0, &mut clones)?;
+    let entries = clones.into_iter().filter_map(|path| {
+        let config = std::fs::read_to_string(path.join(".git").join("config")).ok()?;
+        Some(Entry {
+            id: RepoId::parse(&remote_url(&config)?)?,
+            path: Some(path),
+            ..Default::default()
+        })
+    });
+    Ok(dedup(entries))
+}
+
+fn walk(dir: &Path, depth: usize, clones: &mut Vec<PathBuf>) -> Result<(), Error> {
+    if dir.join(".git").is_dir() {
+        // Submodules and vendored clones inside belong to this one
+        clones.push(dir.to_path_buf());
+        return Ok(());
+    }
+    if depth >= MAX_DEPTH {
+        return Ok(());
+    }
+    let mut children: Vec<_> = std::fs::read_dir(dir)?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.path())
+        .collect();
+    children.sort();
+    for child in children {
+        let name = child
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if name.starts_with('.') || SKIP.contains(&name) {
+            continue;
+        }
+        // Unreadable directories are skipped, not fatal
+        let _ = walk(&child, depth + 1, clones);
+    }
+    Ok(())
+}
+
+/// The `origin` remote, or `upstream` for clones without one.
+fn remote_url(config: &str) -> Option<String> {
+    let mut remotes: HashMap<&str, &str> = HashMap::new();
+    let mut remote = None;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            // `[remote "origin"]`
+            remote = line
+                .strip_prefix("[remote \"")
+                .and_then(|rest| rest.strip_suffix("\"]"));
+            continue;
+        }
+        let Some(remote) = remote else { continue };
+        if let Some((key, value)) = line.split_once('=')
+            && key.trim() == "url"
+        {
+            remotes.entry(remote).or_insert(value.trim());
+        }
+    }
+    ["origin", "upstream"]
+        .iter()
+        .find_map(|name| remotes.get(name))
+        .map(|url| url.to_string())
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use crate::Error;
+use crate::forge::RepoId;
+use crate::input::{Entry, dedup};
+use crate::stats::{Problem, ProblemKind};
+
+pub fn from_manifest(path: &Path, unresolved: &mut Vec<Problem>) -> Result<Vec<Entry>, Error> {
+    Ok(parse(&std::fs::read_to_string(path)?, unresolved))
+}
+
+/// Every module in the `require` directives of a `go.mod`, direct or indirect.
+/// Modules on hosts without a known repo go to `unresolved`.
+fn parse(content: &str, unresolved: &mut Vec<Problem>) -> Vec<Entry> {
+    let mut in_block = false;
+    let mut entries = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let module = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line.split_whitespace().next()
+        } else if let Some(rest) = line.strip_prefix("require") {
+            match rest.trim() {
+                "(" => {
+                    in_block = true;
+                    continue;
+                }
+                rest => rest.split_whitespace().next(),
+            }
+        } else {
+            None
+        };
+        let Some(module) = module else { continue };
+        let Some(id) = module_repo(module) else {
+            unresolved.push(Problem {
+                entry: Entry {
+                    line: Some(i + 1),
+                    packages: vec![module.to_string()],
+                    ..Default::default()
+                },
+                kind: ProblemKind::Unresolved,
+            });
+            continue;
+        };
+        entries.push(Entry {
+            id,
+            line: Some(i + 1),
+            packages: vec![module.to_string()],
+            ..Default::default()
+        });
+    }
+    dedup(entries)
+}
+
+/// Module paths name their repo, e.g. `github.com/o/r/v2`.
+/// The vanity hosts most modules go through are mapped to their repos.
+fn module_repo(module: &str) -> Option<RepoId> {
+    if let Some(name) = module.strip_prefix("golang.org/x/") {
+        let name = name.split('/').next()?;
+        return RepoId::parse(&format!("github.com/golang/{name}"));
+    }
+    // `k8s.io/client-go` is `kubernetes/client-go`, `go.uber.org/zap` is `uber-go/zap`
+    for (host, owner) in [("k8s.io/", "kubernetes"), ("go.uber.org/", "uber-go")] {
+        if let Some(name) = module.strip_prefix(host) {
+            let name = name.split('/').next()?;
+            return RepoId::parse(&format!("github.com/{owner}/{name}"));
+        }
+    }
+    if let Some(path) = module.strip_prefix("gopkg.in/") {
+        // `gopkg.in/yaml.v3` is `go-yaml/yaml`, `gopkg.in/o/r.v1` is `o/r`
+        let path = path.split('/').take(2).collect::<Vec<_>>();
+        let strip = |pkg: &str| {
+            pkg.rsplit_once(".v")
+                .map_or(pkg, |(name, _)| name)
+                .to_string()
+        };
+        let (owner, name) = match path.as_slice() {
+            [pkg] => (format!("go-{}", strip(pkg)), strip(pkg)),
+            [owner, pkg] => (owner.to_string(), strip(pkg)),
+            _ => return None,
+        };
+        return RepoId::parse(&format!("github.com/{owner}/{name}"));
+    }
+    RepoId::parse(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_repos() {
+        let cases = [
+            ("github.com/o/r", Some("o/r")),
+            ("github.com/o/r/v2", Some("o/r")),
+            ("golang.org/x/sync", Some("golang/sync")),
+            ("golang.org/x/tools/gopls", Some("golang/tools")),
+            ("gopkg.in/yaml.v3", Some("go-yaml/yaml")),
+            ("gopkg.in/o/r.v1", Some("o/r")),
+            ("k8s.io/client-go", Some("kubernetes/client-go")),
+            ("go.uber.org/zap", Some("uber-go/zap")),
+            ("gitlab.com/g/p", Some("gitlab.com/g/p")),
+            ("cloud.google.com/go/storage", None),
+        ];
+        for (module, expected) in cases {
+            let id = module_repo(module).map(|id| id.to_string());
+            assert_eq!(id.as_deref(), expected, "{module}");
+        }
+    }
+
+    #[test]
+    fn parse_go_mod() {
+        let content = "module example.com/me\n\
+            \n\
+            require github.com/o/single v1.0.0\n\
+            \n\
+            require (\n\
+            \tgithub.com/o/r v1.2.3 // indirect\n\
+            \tcloud.google.com/go v0.110.0\n\
+            \tgithub.com/o/r/v2 v2.0.0\n\
+            )\n";
+        let mut unresolved = Vec::new();
+        let entries = parse(content, &mut unresolved);
+        let found: Vec<_> = entries
+            .iter()
+            .map(|e| (e.id.to_string(), e.line, e.packages.clone()))
+            .collect();
+        let expected = [
+            (
+                "o/single".to_string(),
+                Some(3),
+                vec!["github.com/o/single".to_string()],
+            ),
+            (
+                "o/r".to_string(),
+                Some(6),
+                vec![
+                    "github.com/o/r".to_string(),
+                    "github.com/o/r/v2".to_string(),
+                ],
+            ),
+        ];
+        assert_eq!(found, expected);
+        let unresolved: Vec<_> = unresolved
+            .iter()
+            .map(|p| (p.entry.line, p.entry.packages.clone()))
+            .collect();
+        assert_eq!(
+            unresolved,
+            [(Some(7), vec!["cloud.google.com/go".to_string()])]
+        );
+    }
+}
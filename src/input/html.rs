@@ -0,0 +1,256 @@
+use regex::Regex;
+use reqwest::Url;
+
+use crate::forge::RepoId;
+use crate::input::{Entry, annotation, dedup};
+
+/// Tags that can appear inside a link's description without ending it.
+/// `<DD>` holds the description in bookmark files.
+const INLINE: [&str; 12] = [
+    "b", "i", "em", "strong", "code", "span", "small", "img", "kbd", "abbr", "mark", "dd",
+];
+
+/// Is this an HTML page rather than markdown or plain text?
+pub fn detect(content: &str) -> bool {
+    let start = content
+        .trim_start()
+        .get(..100)
+        .unwrap_or(content.trim_start())
+        .to_lowercase();
+    start.starts_with("<!doctype") || start.starts_with("<html")
+}
+
+/// Find the repos linked from `<a href>`, resolving relative links against the page.
+/// Sections come from the `<h1>`-`<h6>` headings, or from the folders of a
+/// Netscape bookmark file, as exported by Firefox and Chrome.
+/// Links back to the page's own repo, or inside `<header>`, `<nav>` and
+/// `<footer>`, are navigation, not part of the list.
+pub fn parse(content: &str, page: Option<&Url>) -> Vec<Entry> {
+    let tags = Regex::new(r"(?s)<!--.*?-->|<(/?)([a-zA-Z][a-zA-Z0-9]*)\b([^>]*)>").unwrap();
+    let href_attr = Regex::new(r#"(?i)\bhref\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap();
+    let bookmarks = content.contains("NETSCAPE-Bookmark-file");
+    let titles = tags
+        .captures_iter(content)
+        .filter(|c| {
+            c.get(2)
+                .is_some_and(|name| name.as_str().eq_ignore_ascii_case("h1"))
+        })
+        .count()
+        / 2;
+
+    let mut base = page.cloned();
+    let mut own = page.and_then(|page| RepoId::parse(page.as_str()));
+    // Open sections, as `(level, title)`. Bookmark folders nest by `<DL>` instead.
+    let mut sections: Vec<(usize, String)> = Vec::new();
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut folder = None;
+    let mut heading: Option<(usize, usize)> = None;
+    let mut entries: Vec<Entry> = Vec::new();
+    let (mut line, mut counted) = (1, 0);
+    // Where the label of the open `<a>`, or the description after it, starts
+    let mut label_at: Option<usize> = None;
+    let mut annotation_at: Option<usize> = None;
+    // How many `<header>`, `<nav>` or `<footer>` elements are open
+    let mut chrome = 0usize;
+
+    for tag in tags.captures_iter(content) {
+        let (Some(closing), Some(name)) = (tag.get(1), tag.get(2)) else {
+            continue; // comment
+        };
+        let closing = !closing.as_str().is_empty();
+        let name = name.as_str().to_lowercase();
+        let attrs = tag.get(3).map_or("", |a| a.as_str());
+        let whole = tag.get(0).unwrap();
+
+        if let Some(start) = annotation_at
+            && !INLINE.contains(&name.as_str())
+        {
+            annotation_at = None;
+            if let Some(entry) = entries.last_mut() {
+                entry.annotation = annotation(&text(&tags, &content[start..whole.start()]));
+            }
+        }
+
+        match name.as_str() {
+            "header" | "nav" | "footer" => match closing {
+                true => chrome = chrome.saturating_sub(1),
+                false => chrome += 1,
+            },
+            _ if chrome > 0 => {}
+            "base" => {
+                if let Some(href) = attribute(&href_attr, attrs) {
+                    base = resolve(base.as_ref(), &href).or(base);
+                    own = base.as_ref().and_then(|base| RepoId::parse(base.as_str()));
+                }
+            }
+            "a" if closing => {
+                let Some(start) = label_at.take() else {
+                    continue;
+                };
+                if let Some(entry) = entries.last_mut() {
+                    let label = text(&tags, &content[start..whole.start()]);
+                    entry.label = (!label.is_empty()).then_some(label);
+                }
+                annotation_at = Some(whole.end());
+            }
+            "a" => {
+                label_at = None;
+                let Some(href) = attribute(&href_attr, attrs) else {
+                    continue;
+                };
+                let Some(url) = resolve(base.as_ref(), &href) else {
+                    continue;
+                };
+                let Some(id) = RepoId::parse(url.as_str()) else {
+                    continue;
+                };
+                if own.as_ref().is_some_and(|own| own.key() == id.key()) {
+                    continue;
+                }
+                let category = match bookmarks {
+                    true => folders.iter().flatten().cloned().collect(),
+                    false => sections.iter().map(|(_, title)| title.clone()).collect(),
+                };
+                line += content[counted..whole.start()].matches('\n').count();
+                counted = whole.start();
+                entries.push(Entry {
+                    id,
+                    line: Some(line),
+                    category,
+                    ..Default::default()
+                });
+                label_at = Some(whole.end());
+            }
+            "dl" if bookmarks => {
+                if closing {
+                    folders.pop();
+                } else {
+                    folders.push(folder.take());
+                }
+            }
+            h if h.len() == 2 && h.starts_with('h') => {
+                let Some(level) = h[1..].parse::<usize>().ok().filter(|l| (1..=6).contains(l))
+                else {
+                    continue;
+                };
+                if !closing {
+                    heading = Some((level, whole.end()));
+                    continue;
+                }
+                let Some((level, start)) = heading.take() else {
+                    continue;
+                };
+                let title = text(&tags, &content[start..whole.start()]);
+                if bookmarks {
+                    // `<H1>` is the file's title, `<H3>` names the folder of the next `<DL>`
+                    if level == 3 {
+                        folder = Some(title);
+                    }
+                    continue;
+                }
+                sections.retain(|(open, _)| *open < level);
+                // A lone `<h1>` names the whole page rather than a section
+                if !(titles == 1 && level == 1) {
+                    sections.push((level, title));
+                }
+            }
+            _ => {}
+        }
+    }
+    dedup(entries)
+}
+
+fn attribute(re: &Regex, attrs: &str) -> Option<String> {
+    let caps = re.captures(attrs)?;
+    let value = caps.get(1).or(caps.get(2)).or(caps.get(3))?.as_str();
+    Some(decode_entities(value))
+}
+
+fn resolve(base: Option<&Url>, href: &str) -> Option<Url> {
+    match base {
+        Some(base) => base.join(href).ok(),
+        None => Url::parse(href).ok(),
+    }
+}
+
+/// Text content of an element, without the tags inside it.
+fn text(tags: &Regex, html: &str) -> String {
+    let text = tags.replace_all(html, "");
+    let text = decode_entities(&text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The named entities that show up in links and titles, plus numeric ones.
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                _ => {
+                    let code = entity.strip_prefix('#')?;
+                    let code = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_html() {
+        assert!(detect("<!DOCTYPE html><html></html>"));
+        assert!(detect("\n  <html><body></body></html>"));
+        assert!(!detect("# Awesome\n- https://github.com/a/foo"));
+    }
+
+    #[test]
+    fn skip_navigation() {
+        let page = r#"<html><header><a href="/solutions/industry/healthcare">Healthcare</a></header>
+<nav><a href="https://github.com/o/r">Code</a></nav>
+<article><h2>Web</h2><a href="https://github.com/a/foo">foo</a> - Fast thing</article>
+<footer><a href="/resources/articles">Articles</a></footer></html>"#;
+        let base = Url::parse("https://github.com/o/r").unwrap();
+        let entries = parse(page, Some(&base));
+        let found: Vec<_> = entries
+            .iter()
+            .map(|e| (e.id.to_string(), e.category.clone(), e.label.clone()))
+            .collect();
+        let expected = [(
+            "a/foo".to_string(),
+            vec!["Web".to_string()],
+            Some("foo".to_string()),
+        )];
+        assert_eq!(found, expected);
+        assert_eq!(entries[0].annotation.as_deref(), Some("Fast thing"));
+    }
+}
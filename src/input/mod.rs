@@ -0,0 +1,560 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+mod cargo;
+pub mod editor;
+mod git;
+mod go;
+mod html;
+mod npm;
+mod python;
+
+use octocrab::Octocrab;
+
+use crate::Error;
+use crate::forge::RepoId;
+use crate::forge::github::{self, ListOptions, Listing};
+use crate::stats::{Problem, Repo};
+
+/// A repository mentioned in the input.
+#[derive(Debug, Clone, Default)]
+pub struct Entry {
+    pub id: RepoId,
+    /// Line of the input the repo was found on, starting at 1,
+    /// `None` for inputs without lines such as manifests and listings
+    pub line: Option<usize>,
+    /// Markdown headings the repo was listed under, outermost first
+    pub category: Vec<String>,
+    /// Every input that mentions the repo, in the order they were given
+    pub sources: Vec<String>,
+    /// Packages published from the repo, e.g. crates from a `Cargo.toml`
+    pub packages: Vec<String>,
+    /// Local clone the repo was found in
+    pub path: Option<PathBuf>,
+    /// Text of the link, `foo` in `[foo](https://github.com/a/foo)`
+    pub label: Option<String>,
+    /// Hand-written description following the link, `Fast thing` in `[foo](...) - Fast thing`
+    pub annotation: Option<String>,
+    /// Stats that came along with the input, e.g. from a search, no need to fetch them
+    pub repo: Option<Repo>,
+}
+
+enum Input {
+    Url(String),
+    File(PathBuf),
+    Manifest(Manifest, PathBuf),
+    /// A tree of git clones
+    Dir(PathBuf),
+    Stdin,
+    Editor,
+    GitHub(Listing),
+    Search(String),
+}
+
+impl Input {
+    /// Short name shown in the Source column.
+    fn label(&self) -> String {
+        match self {
+            Input::Url(url) => url
+                .split_once("://")
+                .map_or(url.as_str(), |(_, rest)| rest)
+                .into(),
+            Input::File(path) | Input::Manifest(_, path) | Input::Dir(path) => {
+                path.display().to_string()
+            }
+            Input::Stdin => "stdin".into(),
+            Input::Editor => "editor".into(),
+            Input::GitHub(listing) => listing.to_string(),
+            Input::Search(query) => format!("search:{query}"),
+        }
+    }
+
+    async fn read(
+        self,
+        oct: &Octocrab,
+        opts: ListOptions,
+        unresolved: &mut Vec<Problem>,
+    ) -> Result<Vec<Entry>, Error> {
+        match self {
+            Input::Url(url) => {
+                let response = reqwest::get(&url).await?;
+                // Redirects move the page, relative links follow it
+                let page = response.url().clone();
+                let is_html = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|t| t.to_str().ok())
+                    .is_some_and(|t| t.starts_with("text/html"));
+                let content = response.text().await?;
+                if is_html || html::detect(&content) {
+                    return Ok(html::parse(&content, Some(&page)));
+                }
+                Ok(parse_repos(&content))
+            }
+            Input::File(path) => {
+                let content = std::fs::read_to_string(&path)?;
+                let is_html = path
+                    .extension()
+                    .is_some_and(|ext| ext == "html" || ext == "htm");
+                // Bookmark exports and saved pages
+                if is_html || html::detect(&content) {
+                    return Ok(html::parse(&content, None));
+                }
+                // A saved `cargo metadata` output, other JSON is scanned for links
+                if path.extension().is_some_and(|ext| ext == "json")
+                    && let Ok(entries) = cargo::parse(&content)
+                {
+                    return Ok(entries);
+                }
+                Ok(parse_repos(&content))
+            }
+            Input::Manifest(manifest, path) => manifest.read(&path, unresolved),
+            Input::Dir(path) => git::from_dir(&path),
+            Input::Stdin => {
+                let content = std::io::read_to_string(std::io::stdin())?;
+                // `cargo metadata --format-version 1 | repotablo`, unlike `curl .../api | repotablo`
+                if content.trim_start().starts_with('{')
+                    && let Ok(entries) = cargo::parse(&content)
+                {
+                    return Ok(entries);
+                }
+                if html::detect(&content) {
+                    return Ok(html::parse(&content, None));
+                }
+                Ok(parse_piped(&content))
+            }
+            Input::Editor => editor::open(),
+            Input::GitHub(listing) => {
+                let ids = github::list(oct, &listing, opts).await?;
+                let entries = ids.into_iter().map(|id| Entry {
+                    id,
+                    ..Default::default()
+                });
+                Ok(entries.collect())
+            }
+            Input::Search(query) => {
+                let repos = github::search(oct, &query, opts).await?;
+                let entries = repos.into_iter().map(|repo| Entry {
+                    id: repo.id.clone(),
+                    repo: Some(repo),
+                    ..Default::default()
+                });
+                Ok(entries.collect())
+            }
+        }
+    }
+}
+
+/// Dependency manifests, listing the repos behind each dependency.
+#[derive(Debug, Clone, Copy)]
+enum Manifest {
+    Cargo,
+    Npm,
+    Go,
+    Python,
+}
+
+impl Manifest {
+    fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        match name {
+            "Cargo.toml" | "Cargo.lock" => Some(Self::Cargo),
+            "package.json" => Some(Self::Npm),
+            "go.mod" => Some(Self::Go),
+            "pyproject.toml" => Some(Self::Python),
+            _ if name.starts_with("requirements") && name.ends_with(".txt") => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    fn read(self, path: &Path, unresolved: &mut Vec<Problem>) -> Result<Vec<Entry>, Error> {
+        match self {
+            Self::Cargo => cargo::from_manifest(path),
+            Self::Npm => npm::from_manifest(path),
+            Self::Go => go::from_manifest(path, unresolved),
+            Self::Python => python::from_manifest(path),
+        }
+    }
+}
+
+/// Read every input and merge their repos.
+/// A repo mentioned by several inputs is kept at its first mention, remembering all of them.
+/// `oct` expands the `org:`, `user:`, `stars:` and `search:` inputs, as `opts` says.
+/// Dependencies that couldn't be traced to a repo come back as problems.
+pub async fn get_repos(
+    args: Vec<String>,
+    oct: &Octocrab,
+    opts: ListOptions,
+) -> Result<(Vec<Entry>, Vec<Problem>), Error> {
+    let inputs: Vec<_> = if args.is_empty() {
+        vec![detect_input(None)]
+    } else {
+        args.into_iter()
+            .map(|arg| detect_input(Some(arg)))
+            .collect()
+    };
+    if inputs.iter().filter(|i| matches!(i, Input::Stdin)).count() > 1 {
+        return Err(Error::InvalidArgument(
+            "stdin (`-`) can only be given once".into(),
+        ));
+    }
+
+    let (mut entries, mut unresolved) = (Vec::new(), Vec::new());
+    for input in inputs {
+        let label = input.label();
+        let known = unresolved.len();
+        let read = input.read(oct, opts, &mut unresolved).await?;
+        entries.extend(read.into_iter().map(|entry| Entry {
+            sources: vec![label.clone()],
+            ..entry
+        }));
+        for problem in &mut unresolved[known..] {
+            problem.entry.sources = vec![label.clone()];
+        }
+    }
+    Ok((dedup(entries), unresolved))
+}
+
+fn detect_input(arg: Option<String>) -> Input {
+    match arg {
+        Some(s) if s == "-" => Input::Stdin,
+        Some(s) if let Some(listing) = Listing::parse(&s) => Input::GitHub(listing),
+        // `search:"language:rust stars:>500"`, the shell usually eats the quotes
+        Some(ref s) if let Some(query) = s.strip_prefix("search:") => {
+            Input::Search(query.trim().trim_matches('"').to_string())
+        }
+        Some(s) if s.starts_with("http://") || s.starts_with("https://") => Input::Url(s),
+        Some(s) => {
+            let path = PathBuf::from(s);
+            match Manifest::detect(&path) {
+                Some(manifest) => Input::Manifest(manifest, path),
+                None if path.is_dir() => Input::Dir(path),
+                None => Input::File(path),
+            }
+        }
+        // `curl ... | repotablo`
+        None if !std::io::stdin().is_terminal() => Input::Stdin,
+        None => Input::Editor,
+    }
+}
+
+/// Like `parse_repos`, but also takes a line of just `owner/name` as a GitHub repo,
+/// optionally followed by a tab and more columns.
+/// That is what `gh search repos` and `gh repo list` print when piped.
+fn parse_piped(content: &str) -> Vec<Entry> {
+    let shorthand = regex::Regex::new(r"^([A-Za-z0-9-]+)/([A-Za-z0-9_.-]+)(?:\t|$)").unwrap();
+    let bare = content.lines().enumerate().filter_map(|(i, line)| {
+        let caps = shorthand.captures(line)?;
+        Some(Entry {
+            id: RepoId::parse(&format!("github.com/{}/{}", &caps[1], &caps[2]))?,
+            line: Some(i + 1),
+            ..Default::default()
+        })
+    });
+    let mut entries: Vec<_> = parse_repos(content).into_iter().chain(bare).collect();
+    entries.sort_by_key(|entry| entry.line);
+    dedup(entries)
+}
+
+/// Find links to repos on any supported forge, in any form `RepoId::parse` understands.
+/// Each repo remembers the markdown section it was listed under,
+/// along with the label and description the list gives it.
+/// Repos listed more than once are kept at their first mention.
+pub fn parse_repos(content: &str) -> Vec<Entry> {
+    let re = regex::Regex::new(
+        r#"(?i)(?:(?:https?|ssh|git)://|git@)?[a-z0-9-]+(?:\.[a-z0-9-]+)+[:/][^\s<>()\[\]"'`]+"#,
+    )
+    .unwrap();
+    // The label may itself be a badge, `[![badge](...)](https://github.com/a/foo)`
+    let link =
+        regex::Regex::new(r"!?\[((?:[^\[\]]|!\[[^\]]*\]\([^)]*\))*)\]\(([^)\s]*)[^)]*\)").unwrap();
+    let mut sections = Sections::new(content);
+    let mut entries = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if sections.update(line) {
+            continue;
+        }
+        let links: Vec<_> = link.captures_iter(line).collect();
+        let repos: Vec<_> = re
+            .find_iter(line)
+            .filter_map(|m| Some((m, RepoId::parse(m.as_str())?)))
+            .collect();
+        // The markdown link each URL is the target of, if any
+        let link_of = |url: &regex::Match| links.iter().find(|l| contains(l, url));
+        for (j, (url, id)) in repos.iter().enumerate() {
+            let link = link_of(url);
+            let label = link
+                .filter(|l| !l[0].starts_with('!'))
+                .map(|l| clean_markup(&l[1]))
+                .filter(|label| !label.is_empty());
+            // The description runs up to the next repo
+            let end = link.map_or(url.end(), |l| l.get(0).unwrap().end());
+            let next = repos.get(j + 1).map_or(line.len(), |(next, _)| {
+                link_of(next).map_or(next.start(), |l| l.get(0).unwrap().start())
+            });
+            // Table cells are columns of their own, not a description
+            let text = line.get(end..next).unwrap_or_default();
+            let text = text.split('|').next().unwrap_or_default();
+            // Prose around a bare URL isn't one either, unless it's set off like one
+            let separated = text.trim_start().starts_with(['-', '–', '—', ':']);
+            let annotation = (link.is_some() || separated)
+                .then(|| annotation(text))
+                .flatten();
+            entries.push(Entry {
+                id: id.clone(),
+                line: Some(i + 1),
+                category: sections.path(),
+                label,
+                annotation,
+                ..Default::default()
+            });
+        }
+    }
+    dedup(entries)
+}
+
+/// Is `url` the target of the markdown `link`?
+fn contains(link: &regex::Captures, url: &regex::Match) -> bool {
+    link.get(2)
+        .is_some_and(|target| target.start() <= url.start() && url.end() <= target.end())
+}
+
+/// Plain text out of a markdown snippet: links become their labels, badges and emphasis go away.
+fn clean_markup(text: &str) -> String {
+    static IMAGE: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"!\[[^\]]*\]\([^)]*\)").unwrap());
+    static LINK: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap());
+    let text = IMAGE.replace_all(text, "");
+    let text = LINK.replace_all(&text, "$1");
+    let text = text.replace(['*', '`'], "").replace("__", "");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The description after a link, without the dash or colon separating them.
+fn annotation(text: &str) -> Option<String> {
+    let text = clean_markup(text);
+    let text = text
+        .trim_start_matches(|c: char| matches!(c, '-' | '–' | '—' | ':' | ',') || c.is_whitespace())
+        .trim_end();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Keep each repo at its first mention, gathering the sources and packages of the later ones.
+fn dedup(entries: impl IntoIterator<Item = Entry>) -> Vec<Entry> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut kept: Vec<Entry> = Vec::new();
+    for entry in entries {
+        match seen.get(&entry.id.key()) {
+            Some(&i) => {
+                extend_unique(&mut kept[i].sources, entry.sources);
+                extend_unique(&mut kept[i].packages, entry.packages);
+            }
+            None => {
+                seen.insert(entry.id.key(), kept.len());
+                kept.push(entry);
+            }
+        }
+    }
+    kept
+}
+
+fn extend_unique(into: &mut Vec<String>, items: Vec<String>) {
+    for item in items {
+        if !into.contains(&item) {
+            into.push(item);
+        }
+    }
+}
+
+/// Tracks the markdown heading hierarchy while walking a document line by line.
+struct Sections {
+    /// Open headings, as `(level, title)`
+    stack: Vec<(usize, String)>,
+    /// A lone `# Title` names the whole list rather than a section
+    skip_title: bool,
+    in_code_block: bool,
+    link: regex::Regex,
+}
+
+impl Sections {
+    fn new(content: &str) -> Self {
+        let mut in_code_block = false;
+        let titles = content
+            .lines()
+            .filter(|line| {
+                if Self::is_fence(line) {
+                    in_code_block = !in_code_block;
+                }
+                !in_code_block && Self::heading(line).is_some_and(|(level, _)| level == 1)
+            })
+            .count();
+        Self {
+            stack: Vec::new(),
+            skip_title: titles == 1,
+            in_code_block: false,
+            link: regex::Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap(),
+        }
+    }
+
+    /// Returns `true` if the line is a heading.
+    /// `#` lines inside code blocks are comments, not headings.
+    fn update(&mut self, line: &str) -> bool {
+        if Self::is_fence(line) {
+            self.in_code_block = !self.in_code_block;
+        }
+        if self.in_code_block {
+            return false;
+        }
+        let Some((level, title)) = Self::heading(line) else {
+            return false;
+        };
+        self.stack.retain(|(open, _)| *open < level);
+        if !(self.skip_title && level == 1) {
+            let title = self.link.replace_all(title, "$1");
+            let title = title.trim_matches(|c: char| c == '*' || c == '_' || c.is_whitespace());
+            self.stack.push((level, title.to_string()));
+        }
+        true
+    }
+
+    fn path(&self) -> Vec<String> {
+        self.stack.iter().map(|(_, title)| title.clone()).collect()
+    }
+
+    fn is_fence(line: &str) -> bool {
+        line.trim_start().starts_with("```")
+    }
+
+    /// ATX headings only, `# Title` through `###### Title`.
+    fn heading(line: &str) -> Option<(usize, &str)> {
+        let level = line.chars().take_while(|&c| c == '#').count();
+        let title = line[level..].strip_prefix(' ')?;
+        (1..=6)
+            .contains(&level)
+            .then(|| (level, title.trim().trim_end_matches('#').trim_end()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Repo, line, category, label and annotation.
+    type Expected<'a> = (
+        &'a str,
+        usize,
+        &'a [&'a str],
+        Option<&'a str>,
+        Option<&'a str>,
+    );
+
+    #[test]
+    fn parse_repos_in_lists() {
+        let cases: [(&str, &[Expected]); 10] = [
+            (
+                "- [foo](https://github.com/a/foo) - Fast thing",
+                &[("a/foo", 1, &[], Some("foo"), Some("Fast thing"))],
+            ),
+            (
+                "see github.com/a/foo and git@gitlab.com:g/bar.git",
+                &[
+                    ("a/foo", 1, &[], None, None),
+                    ("gitlab.com/g/bar", 1, &[], None, None),
+                ],
+            ),
+            (
+                "- https://github.com/a/foo - Fast thing",
+                &[("a/foo", 1, &[], None, Some("Fast thing"))],
+            ),
+            (
+                "| [foo](https://github.com/a/foo) | desc | ★ |",
+                &[("a/foo", 1, &[], Some("foo"), None)],
+            ),
+            (
+                "# Awesome\n## Web\n### Servers\n- https://github.com/a/foo\n## CLI\n- https://github.com/a/bar",
+                &[
+                    ("a/foo", 4, &["Web", "Servers"], None, None),
+                    ("a/bar", 6, &["CLI"], None, None),
+                ],
+            ),
+            (
+                "# One\n- https://github.com/a/foo\n# Two\n- https://github.com/a/bar",
+                &[
+                    ("a/foo", 2, &["One"], None, None),
+                    ("a/bar", 4, &["Two"], None, None),
+                ],
+            ),
+            (
+                "# Awesome\n```sh\n# install\n```\n## Web\n- [foo](https://github.com/a/foo)",
+                &[("a/foo", 6, &["Web"], Some("foo"), None)],
+            ),
+            (
+                "- [![badge](https://img.shields.io/x)](https://github.com/a/foo)",
+                &[("a/foo", 1, &[], None, None)],
+            ),
+            (
+                "- https://github.com/a/foo\n- https://github.com/A/Foo/issues",
+                &[("a/foo", 1, &[], None, None)],
+            ),
+            (
+                "- https://gitlab.com/g/p/issues/3\n- https://gitlab.com/g/q/-/tree/main",
+                &[
+                    ("gitlab.com/g/p", 1, &[], None, None),
+                    ("gitlab.com/g/q", 2, &[], None, None),
+                ],
+            ),
+        ];
+        for (content, expected) in cases {
+            let entries = parse_repos(content);
+            let found: Vec<_> = entries
+                .iter()
+                .map(|e| {
+                    (
+                        e.id.to_string(),
+                        e.line,
+                        e.category.clone(),
+                        e.label.clone(),
+                        e.annotation.clone(),
+                    )
+                })
+                .collect();
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|(id, line, category, label, annotation)| {
+                    (
+                        id.to_string(),
+                        Some(*line),
+                        category.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+                        label.map(String::from),
+                        annotation.map(String::from),
+                    )
+                })
+                .collect();
+            assert_eq!(found, expected, "{content}");
+        }
+    }
+
+    #[test]
+    fn parse_piped_shorthand() {
+        let cases: [(&str, &[&str]); 5] = [
+            ("a/foo\nb/bar", &["a/foo", "b/bar"]),
+            ("a/foo\tA fast thing\tpublic", &["a/foo"]),
+            ("TCP/IP stack notes", &[]),
+            ("src/main.rs:12: warning", &[]),
+            (
+                "a/foo\nhttps://gitlab.com/g/p",
+                &["a/foo", "gitlab.com/g/p"],
+            ),
+        ];
+        for (content, expected) in cases {
+            let ids: Vec<_> = parse_piped(content)
+                .iter()
+                .map(|e| e.id.to_string())
+                .collect();
+            assert_eq!(ids, expected, "{content}");
+        }
+    }
+}
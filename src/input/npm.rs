@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::Error;
+use crate::forge::RepoId;
+use crate::input::{Entry, dedup};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageJson {
+    #[serde(default)]
+    dependencies: Map<String, Value>,
+    #[serde(default)]
+    dev_dependencies: Map<String, Value>,
+    repository: Option<Repository>,
+}
+
+/// `"repository": "github:o/r"` or `"repository": { "type": "git", "url": "..." }`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Repository {
+    Shorthand(String),
+    Full { url: String },
+}
+
+/// Dependencies and dev dependencies of a `package.json`.
+/// Their repos come from the `repository` field in `node_modules`, so install them first.
+pub fn from_manifest(path: &Path) -> Result<Vec<Entry>, Error> {
+    let manifest: PackageJson = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let node_modules = path.with_file_name("node_modules");
+
+    let dependencies = manifest
+        .dependencies
+        .into_iter()
+        .chain(manifest.dev_dependencies);
+    let entries = dependencies.filter_map(|(name, spec)| {
+        // Dependencies installed straight from a repo, e.g. `github:o/r#main`
+        let id = spec
+            .as_str()
+            .and_then(repo_url)
+            .or_else(|| installed_repo(&node_modules, &name))?;
+        Some(Entry {
+            id,
+            packages: vec![name],
+            ..Default::default()
+        })
+    });
+    Ok(dedup(entries))
+}
+
+fn installed_repo(node_modules: &Path, name: &str) -> Option<RepoId> {
+    let path = node_modules.join(name).join("package.json");
+    let package: PackageJson = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    match package.repository? {
+        Repository::Shorthand(url) | Repository::Full { url } => repo_url(&url),
+    }
+}
+
+/// npm's repository forms: full git URLs, `github:o/r`, `gitlab:o/r` and bare `o/r` for GitHub.
+fn repo_url(url: &str) -> Option<RepoId> {
+    let url = url.strip_prefix("git+").unwrap_or(url);
+    let url = url.split('#').next()?;
+    if let Some(path) = url.strip_prefix("github:") {
+        return RepoId::parse(&format!("github.com/{path}"));
+    }
+    if let Some(path) = url.strip_prefix("gitlab:") {
+        return RepoId::parse(&format!("gitlab.com/{path}"));
+    }
+    let shorthand =
+        url.split('/').count() == 2 && !url.starts_with('.') && !url.contains([':', '@', ' ']);
+    if shorthand {
+        return RepoId::parse(&format!("github.com/{url}"));
+    }
+    RepoId::parse(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repository_urls() {
+        let cases = [
+            ("git+https://github.com/o/r.git", Some("o/r")),
+            ("git://github.com/o/r.git#main", Some("o/r")),
+            ("git@github.com:o/r.git", Some("o/r")),
+            ("github:o/r", Some("o/r")),
+            ("github:o/r#v1", Some("o/r")),
+            ("gitlab:g/p", Some("gitlab.com/g/p")),
+            ("o/r", Some("o/r")),
+            ("https://gitlab.com/g/sub/p", Some("gitlab.com/g/sub/p")),
+            ("./local/path", None),
+            ("^1.2.3", None),
+            ("npm:other@1", None),
+        ];
+        for (url, expected) in cases {
+            let id = repo_url(url).map(|id| id.to_string());
+            assert_eq!(id.as_deref(), expected, "{url}");
+        }
+    }
+}
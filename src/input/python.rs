@@ -0,0 +1,236 @@
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+use crate::forge::RepoId;
+use crate::input::{Entry, dedup};
+
+/// Where a virtualenv keeps its installed packages, relative to the project.
+const VENVS: [&str; 2] = [".venv", "venv"];
+
+/// Requirements from a `requirements*.txt` or a `pyproject.toml`.
+/// Their repos come from the package metadata in the project's virtualenv, so install them first.
+pub fn from_manifest(path: &Path) -> Result<Vec<Entry>, Error> {
+    let content = std::fs::read_to_string(path)?;
+    let requirements = if path.extension().is_some_and(|ext| ext == "toml") {
+        pyproject(&content)?
+    } else {
+        requirements_txt(&content)
+    };
+    let site_packages = path.parent().and_then(site_packages);
+
+    let entries = requirements.into_iter().filter_map(|(line, requirement)| {
+        let (name, url) = split_requirement(&requirement)?;
+        // `name @ git+https://github.com/o/r` points at the repo already
+        let id = url
+            .and_then(|url| RepoId::parse(strip_revision(url)))
+            .or_else(|| installed_repo(site_packages.as_deref()?, name))?;
+        Some(Entry {
+            id,
+            line,
+            packages: vec![name.to_string()],
+            ..Default::default()
+        })
+    });
+    Ok(dedup(entries))
+}
+
+/// Requirement lines, skipping comments and pip options such as `-r other.txt`.
+fn requirements_txt(content: &str) -> Vec<(Option<usize>, String)> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split(" #").next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with(['#', '-']))
+        .map(|(line, requirement)| (Some(line), requirement.to_string()))
+        .collect()
+}
+
+/// PEP 621 `project.dependencies` and optional dependencies, or Poetry's dependency table.
+fn pyproject(content: &str) -> Result<Vec<(Option<usize>, String)>, Error> {
+    let doc: toml::Table = content
+        .parse()
+        .map_err(|e| Error::InvalidArgument(format!("invalid pyproject.toml: {e}")))?;
+    let table = |value: Option<&toml::Value>| value.and_then(toml::Value::as_table).cloned();
+    let project = table(doc.get("project")).unwrap_or_default();
+
+    let mut requirements: Vec<String> = Vec::new();
+    let strings = |value: &toml::Value| {
+        let items = value.as_array().cloned().unwrap_or_default();
+        items
+            .into_iter()
+            .filter_map(|item| item.as_str().map(String::from))
+            .collect::<Vec<_>>()
+    };
+    if let Some(dependencies) = project.get("dependencies") {
+        requirements.extend(strings(dependencies));
+    }
+    for extra in table(project.get("optional-dependencies"))
+        .unwrap_or_default()
+        .values()
+    {
+        requirements.extend(strings(extra));
+    }
+    let poetry = table(doc.get("tool"))
+        .and_then(|tool| table(tool.get("poetry")))
+        .and_then(|poetry| table(poetry.get("dependencies")))
+        .unwrap_or_default();
+    requirements.extend(poetry.keys().filter(|name| *name != "python").cloned());
+
+    Ok(requirements.into_iter().map(|r| (None, r)).collect())
+}
+
+/// `requests[socks]>=2.0; python_version > "3.8"` is `requests`.
+/// Direct references, `name @ url`, also return the URL.
+fn split_requirement(requirement: &str) -> Option<(&str, Option<&str>)> {
+    if let Some((name, url)) = requirement.split_once(" @ ") {
+        return Some((name.trim(), Some(url.trim())));
+    }
+    // A bare URL, `git+https://github.com/o/r@v1#egg=name`
+    if requirement.contains("://") {
+        let name = requirement.split("#egg=").nth(1).unwrap_or(requirement);
+        return Some((
+            name,
+            Some(requirement.strip_prefix("git+").unwrap_or(requirement)),
+        ));
+    }
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    (!name.is_empty()).then_some((name, None))
+}
+
+/// `https://github.com/o/r@v1` pins a revision, which isn't part of the repo.
+fn strip_revision(url: &str) -> &str {
+    match url.rsplit_once('@') {
+        Some((repo, revision)) if !revision.contains('/') => repo,
+        _ => url,
+    }
+}
+
+fn site_packages(project: &Path) -> Option<PathBuf> {
+    for venv in VENVS {
+        let venv = project.join(venv);
+        // Windows virtualenvs skip the version directory
+        let windows = venv.join("Lib").join("site-packages");
+        if windows.is_dir() {
+            return Some(windows);
+        }
+        let Ok(lib) = std::fs::read_dir(venv.join("lib")) else {
+            continue;
+        };
+        let found = lib
+            .flatten()
+            .map(|python| python.path().join("site-packages"))
+            .find(|path| path.is_dir());
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+/// Find the package's `*.dist-info/METADATA` and take the first repo among its URLs.
+fn installed_repo(site_packages: &Path, name: &str) -> Option<RepoId> {
+    let name = normalize(name);
+    let dist_info = std::fs::read_dir(site_packages)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            let dir = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            dir.strip_suffix(".dist-info")
+                .and_then(|dir| dir.rsplit_once('-'))
+                .is_some_and(|(package, _version)| normalize(package) == name)
+        })?;
+    let metadata = std::fs::read_to_string(dist_info.join("METADATA")).ok()?;
+
+    // `Project-URL: Source, https://...` says the most, `Home-page` comes last
+    let project_urls = metadata
+        .lines()
+        .filter_map(|line| line.strip_prefix("Project-URL:"))
+        .filter_map(|url| url.split_once(',').map(|(_, url)| url.trim()));
+    let home_page = metadata
+        .lines()
+        .filter_map(|line| line.strip_prefix("Home-page:"))
+        .map(str::trim);
+    project_urls.chain(home_page).find_map(RepoId::parse)
+}
+
+/// PEP 503 name normalization, `Foo.Bar_baz` and `foo-bar-baz` are the same package.
+fn normalize(name: &str) -> String {
+    let mut normalized = String::new();
+    for part in name.split(['-', '_', '.']).filter(|p| !p.is_empty()) {
+        if !normalized.is_empty() {
+            normalized.push('-');
+        }
+        normalized.push_str(&part.to_lowercase());
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_requirements() {
+        let cases = [
+            ("requests", Some(("requests", None))),
+            ("requests[socks]>=2.0", Some(("requests", None))),
+            (
+                "Django==4.2; python_version > \"3.8\"",
+                Some(("Django", None)),
+            ),
+            ("zope.interface~=6.0", Some(("zope.interface", None))),
+            (
+                "foo @ git+https://github.com/o/r@v1",
+                Some(("foo", Some("git+https://github.com/o/r@v1"))),
+            ),
+            (
+                "git+https://github.com/o/r@v1#egg=foo",
+                Some(("foo", Some("https://github.com/o/r@v1#egg=foo"))),
+            ),
+            (">=1.0", None),
+        ];
+        for (requirement, expected) in cases {
+            assert_eq!(split_requirement(requirement), expected, "{requirement}");
+        }
+    }
+
+    #[test]
+    fn strip_revisions() {
+        let cases = [
+            ("https://github.com/o/r@v1", "https://github.com/o/r"),
+            ("https://github.com/o/r", "https://github.com/o/r"),
+            (
+                "git+ssh://git@github.com/o/r",
+                "git+ssh://git@github.com/o/r",
+            ),
+            (
+                "git+ssh://git@github.com/o/r@main",
+                "git+ssh://git@github.com/o/r",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(strip_revision(url), expected, "{url}");
+        }
+    }
+
+    #[test]
+    fn normalize_names() {
+        let cases = [
+            ("requests", "requests"),
+            ("Django", "django"),
+            ("zope.interface", "zope-interface"),
+            ("typing_extensions", "typing-extensions"),
+            ("Foo__Bar--baz", "foo-bar-baz"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(normalize(name), expected, "{name}");
+        }
+    }
+}
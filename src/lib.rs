@@ -1,7 +1,12 @@
+pub mod activity;
+pub mod cache;
 pub mod cli;
 pub mod error;
+pub mod forge;
 pub mod input;
+pub mod output;
 pub mod stats;
+pub mod throttle;
 pub mod ui;
 
 pub use error::Error;
@@ -1,47 +1,234 @@
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
 use clap::Parser;
 use octocrab::Octocrab;
 
 use repotablo::{
-    Error,
+    Error, activity,
+    cache::Cache,
     cli::Opts,
-    input::get_repos,
-    stats::Stats,
-    ui::{App, draw::draw_loading},
+    forge::{Clients, github::ListOptions},
+    input::{Entry, get_repos},
+    output::{self, Format},
+    stats::{Backend, Column, FetchOptions, Problem, Progress, Stats},
+    ui::{App, SortBy, draw::draw_loading},
 };
 
 async fn run() -> Result<(), Error> {
     let opts = Opts::parse();
-    let oct = if let Some(token) = opts.github_token {
-        Octocrab::builder().personal_token(token).build()?
+    // GraphQL needs authentication, fall back to REST without a token.
+    let (oct, backend) = if let Some(token) = opts.github_token {
+        (
+            Octocrab::builder().personal_token(token).build()?,
+            Backend::GraphQl,
+        )
     } else {
-        Octocrab::default()
+        (Octocrab::default(), Backend::Rest)
+    };
+    let clients = Clients {
+        github: oct,
+        http: reqwest::Client::builder()
+            .user_agent(concat!("repotablo/", env!("CARGO_PKG_VERSION")))
+            .build()?,
+        gitlab_token: opts.gitlab_token,
+        gitlab_hosts: opts.gitlab_host,
+        srht_token: opts.srht_token,
+    };
+
+    let list_opts = ListOptions {
+        exclude_forks: opts.exclude_forks,
+        exclude_archived: opts.exclude_archived,
+        search_limit: opts.limit,
+    };
+    let (repos, unresolved) = get_repos(opts.input, &clients.github, list_opts).await?;
+    let fetch_opts = FetchOptions {
+        min_stars: opts.min_stars,
+        exclude_forks: opts.exclude_forks,
+        exclude_archived: opts.exclude_archived,
+        jobs: opts.jobs,
+        backend,
+        cache: (!opts.no_cache).then(|| Arc::new(Cache::load(opts.cache_ttl))),
+        wait_on_rate_limit: !opts.no_wait,
+        depth: opts.depth,
     };
 
-    let repos = get_repos(opts.input).await?;
+    let problems = match opts.format {
+        Some(format) => {
+            print_stats(
+                clients,
+                repos,
+                unresolved,
+                fetch_opts,
+                format,
+                opts.sort,
+                &opts.columns,
+            )
+            .await?
+        }
+        None => {
+            run_tui(
+                clients,
+                repos,
+                unresolved,
+                fetch_opts,
+                opts.sort,
+                opts.columns,
+            )
+            .await?
+        }
+    };
 
+    if !problems.is_empty() {
+        eprintln!("Problems with {} repos:", problems.len());
+        for problem in problems {
+            eprintln!("  {problem}");
+        }
+    }
+    Ok(())
+}
+
+/// Non-interactive mode, for pipelines and cron jobs.
+async fn print_stats(
+    clients: Clients,
+    repos: Vec<Entry>,
+    unresolved: Vec<Problem>,
+    fetch_opts: FetchOptions,
+    format: Format,
+    sort_by: Option<SortBy>,
+    columns: &[Column],
+) -> Result<Vec<Problem>, Error> {
+    let (tx, mut rx) = tokio::sync::mpsc::channel(32);
+    // Only show progress to humans, never pollute a redirected stderr.
+    let show_progress = std::io::stderr().is_terminal();
+
+    let fetch = Stats::fetch(&clients, repos, tx, &fetch_opts);
+    let report = async {
+        while let Some(progress) = rx.recv().await {
+            if !show_progress {
+                continue;
+            }
+            match progress {
+                Progress::Fetched { current, total } => {
+                    eprint!("\r\x1b[2KFetching {current}/{total}...")
+                }
+                Progress::Paused { until } => eprint!(
+                    "\r\x1b[2KRate limited, resuming at {}...",
+                    until.with_timezone(&chrono::Local).format("%H:%M:%S")
+                ),
+            }
+            let _ = std::io::stderr().flush();
+        }
+        if show_progress {
+            eprint!("\r\x1b[2K");
+        }
+    };
+    let (stats, ()) = tokio::join!(fetch, report);
+
+    let mut stats = stats?;
+    stats.problems.extend(unresolved);
+
+    // Nothing to show in the meantime, wait for the releases, commit activity and contributors
+    let (tx, mut rx) = tokio::sync::mpsc::channel(32);
+    let pending = activity::Pending::of(&stats.repos);
+    if show_progress && !pending.is_empty() {
+        eprint!("Fetching releases, commit activity and contributors...");
+    }
+    let fetch = activity::fetch(
+        clients.github.clone(),
+        pending,
+        fetch_opts.jobs,
+        fetch_opts.cache.clone(),
+        tx,
+    );
+    let collect = async {
+        while let Some(update) = rx.recv().await {
+            activity::apply(&mut stats.repos, update);
+        }
+    };
+    tokio::join!(fetch, collect);
+    if show_progress {
+        eprint!("\r\x1b[2K");
+    }
+
+    if let Some(sort_by) = sort_by {
+        sort_by.sort(&mut stats.repos);
+    }
+    let mut columns = columns.to_vec();
+    Column::add_present(&mut columns, &stats.repos);
+    let repos: Vec<_> = stats.repos.iter().collect();
+    print!("{}", output::render(&repos, format, &columns)?);
+    Ok(stats.problems)
+}
+
+async fn run_tui(
+    clients: Clients,
+    repos: Vec<Entry>,
+    unresolved: Vec<Problem>,
+    fetch_opts: FetchOptions,
+    sort_by: Option<SortBy>,
+    columns: Vec<Column>,
+) -> Result<Vec<Problem>, Error> {
     // Init ratatui after editor closes, otherwise they fight for terminal control.
+    // When the list was piped in, crossterm reads keys from `/dev/tty` instead of stdin.
     let mut terminal = ratatui::init();
 
     let result = async {
         let (tx, mut rx) = tokio::sync::mpsc::channel(32);
 
-        let fetch_task = tokio::spawn({
-            let repos = repos.clone();
-            async move { Stats::fetch(&oct, repos, tx, opts.min_stars).await }
-        });
+        let (mut current, mut total) = (0, repos.len());
+        let (oct, jobs, cache) = (
+            clients.github.clone(),
+            fetch_opts.jobs,
+            fetch_opts.cache.clone(),
+        );
+        let fetch_task =
+            tokio::spawn(async move { Stats::fetch(&clients, repos, tx, &fetch_opts).await });
 
-        while let Some((current, total)) = rx.recv().await {
-            draw_loading(&mut terminal, current, total)?;
+        let mut paused_until = None;
+        loop {
+            // Wake up every second to keep the rate limit countdown ticking.
+            match tokio::time::timeout(Duration::from_secs(1), rx.recv()).await {
+                Ok(Some(Progress::Fetched {
+                    current: c,
+                    total: t,
+                })) => (current, total) = (c, t),
+                Ok(Some(Progress::Paused { until })) => paused_until = Some(until),
+                Ok(None) => break,
+                Err(_) => {}
+            }
+            paused_until = paused_until.filter(|until| *until > Utc::now());
+            draw_loading(&mut terminal, current, total, paused_until)?;
         }
 
-        let stats = fetch_task
+        let mut stats = fetch_task
             .await
             .map_err(|e| Error::Internal(e.to_string()))??;
-        App::new(stats).run(&mut terminal)?;
-        Ok(())
+        stats.problems.extend(unresolved);
+        let problems = stats.problems.clone();
+
+        // GitHub may take a while to compute commit activity and contributors, show the table in the meantime
+        let (tx, rx) = tokio::sync::mpsc::channel(32);
+        let activity_task = tokio::spawn(activity::fetch(
+            oct,
+            activity::Pending::of(&stats.repos),
+            jobs,
+            cache.clone(),
+            tx,
+        ));
+        App::new(stats, sort_by, columns, rx).run(&mut terminal)?;
+        // Aborting skips the task's own save, keep what it got so far.
+        activity_task.abort();
+        if let Some(cache) = cache {
+            cache.save()?;
+        }
+        Ok(problems)
     }
     .await;
 
+    // Restore before the caller prints the summary, so it stays on screen.
     ratatui::restore();
     result
 }
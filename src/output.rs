@@ -0,0 +1,171 @@
+use clap::ValueEnum;
+
+use crate::Error;
+use crate::stats::{Column, Repo};
+
+/// Output formats for non-interactive mode.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+    Markdown,
+}
+
+/// `columns` are the optional ones to show, JSON and CSV always have every field.
+pub fn render(repos: &[&Repo], format: Format, columns: &[Column]) -> Result<String, Error> {
+    let output = match format {
+        Format::Table => table(repos, columns),
+        Format::Json => serde_json::to_string_pretty(repos)? + "\n",
+        Format::Csv => csv(repos),
+        Format::Markdown => markdown(repos, columns),
+    };
+    Ok(output)
+}
+
+/// Plain text columns, aligned for reading in a terminal.
+fn table(repos: &[&Repo], columns: &[Column]) -> String {
+    let header = Repo::header(columns);
+    let rows: Vec<_> = repos.iter().map(|r| r.row(columns)).collect();
+    let mut widths: Vec<_> = header.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: &[String]| {
+        let cells: Vec<_> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:width$}"))
+            .collect();
+        cells.join("  ").trim_end().to_string() + "\n"
+    };
+    let header: Vec<_> = header.into_iter().map(String::from).collect();
+    let mut out = line(&header);
+    for row in &rows {
+        out.push_str(&line(row));
+    }
+    out
+}
+
+const CSV_HEADER: [&str; 31] = [
+    "forge",
+    "host",
+    "owner",
+    "name",
+    "packages",
+    "stars",
+    "forks",
+    "license",
+    "created_at",
+    "pushed_at",
+    "homepage",
+    "description",
+    "archived",
+    "fork",
+    "parent",
+    "template",
+    "disabled",
+    "visibility",
+    "open_issues",
+    "watchers",
+    "language",
+    "size_kb",
+    "default_branch",
+    "latest_release",
+    "releases_last_year",
+    "commits_last_90_days",
+    "contributors",
+    "bus_factor",
+    "source",
+    "path",
+    "category",
+];
+
+/// Raw values rather than the humanized ones, so spreadsheets can sort them.
+fn csv(repos: &[&Repo]) -> String {
+    let mut out = CSV_HEADER.join(",") + "\n";
+    for r in repos {
+        let fields = [
+            r.id.forge.to_string(),
+            r.id.host.clone(),
+            r.id.owner.clone(),
+            r.id.name.clone(),
+            r.packages.join(" "),
+            r.stars.to_string(),
+            r.forks.to_string(),
+            r.license.clone(),
+            r.created_at.to_rfc3339(),
+            r.pushed_at.to_rfc3339(),
+            r.homepage.clone().unwrap_or_default(),
+            r.description.clone().unwrap_or_default(),
+            r.archived.to_string(),
+            r.fork.to_string(),
+            r.parent.clone().unwrap_or_default(),
+            r.is_template.to_string(),
+            r.disabled.to_string(),
+            r.visibility.clone().unwrap_or_default(),
+            r.open_issues.to_string(),
+            r.watchers.to_string(),
+            r.language.clone().unwrap_or_default(),
+            r.size.to_string(),
+            r.default_branch.clone().unwrap_or_default(),
+            r.releases
+                .as_ref()
+                .and_then(|rel| rel.latest)
+                .map(|date| date.to_rfc3339())
+                .unwrap_or_default(),
+            r.releases
+                .as_ref()
+                .map(|rel| rel.last_year.to_string())
+                .unwrap_or_default(),
+            r.recent_commits()
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            r.contributors
+                .as_ref()
+                .and_then(|c| c.count)
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            r.contributors
+                .as_ref()
+                .map(|c| c.bus_factor.to_string())
+                .unwrap_or_default(),
+            r.source(),
+            r.path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            r.category(),
+        ];
+        let fields: Vec<_> = fields.iter().map(|f| csv_escape(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn markdown(repos: &[&Repo], columns: &[Column]) -> String {
+    let mut md = String::new();
+
+    // header
+    let header = Repo::header(columns);
+    md.push_str(&format!("| {} |\n", header.join(" | ")));
+    let rule: Vec<_> = header.iter().map(|h| "-".repeat(h.len())).collect();
+    md.push_str(&format!("|-{}-|\n", rule.join("-|-")));
+
+    for r in repos {
+        md.push_str(&format!("| {} |\n", r.row(columns).join(" | ")));
+    }
+    md
+}
@@ -1,38 +1,406 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
 use chrono_humanize::HumanTime;
-use octocrab::Octocrab;
+use clap::ValueEnum;
+use futures::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
 use crate::Error;
+use crate::cache::Cache;
+use crate::forge::github::{self, Fetched, graphql};
+use crate::forge::{self, Clients, Forge, RepoId};
+use crate::input::{Entry, parse_repos};
+use crate::throttle::Throttle;
 
 pub struct Stats {
     pub repos: Vec<Repo>,
+    pub problems: Vec<Problem>,
+}
+
+/// A repo from the input that didn't make it into the table as listed.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub entry: Entry,
+    pub kind: ProblemKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProblemKind {
+    NotFound,
+    /// The forge redirected to a new name
+    Renamed(RepoId),
+    Failed(String),
+    /// A dependency that couldn't be traced to a repo, e.g. behind a Go vanity import
+    Unresolved,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = self.entry.sources.first().map_or("", String::as_str);
+        // Without a repo, the package is all there is to name
+        let name = match self.kind {
+            ProblemKind::Unresolved => self.entry.packages.join(", "),
+            _ => self.entry.id.to_string(),
+        };
+        match self.entry.line {
+            Some(line) => write!(f, "{source}:{line}: {name} ")?,
+            None => write!(f, "{source}: {name} ")?,
+        }
+        match &self.kind {
+            ProblemKind::NotFound => write!(f, "not found"),
+            ProblemKind::Renamed(full_name) => write!(f, "renamed to {full_name}"),
+            ProblemKind::Failed(reason) => write!(f, "failed: {reason}"),
+            ProblemKind::Unresolved => write!(f, "has no known repo"),
+        }
+    }
+}
+
+/// First line of an error, octocrab appends a backtrace to some of them.
+fn reason(err: &Error) -> String {
+    let reason = err.to_string();
+    reason.lines().next().unwrap_or_default().to_string()
+}
+
+/// A single lookup either finds the repo or says why it couldn't.
+pub type Lookup = Result<Repo, ProblemKind>;
+
+/// Where GitHub stats come from.
+#[derive(Debug, Clone, Copy)]
+pub enum Backend {
+    /// One REST request per repo. Works without authentication.
+    Rest,
+    /// Dozens of repos per GraphQL query. Requires a token.
+    GraphQl,
+}
+
+/// Knobs for `Stats::fetch`.
+pub struct FetchOptions {
+    pub min_stars: Option<u32>,
+    pub exclude_forks: bool,
+    pub exclude_archived: bool,
+    pub jobs: usize,
+    pub backend: Backend,
+    /// `None` skips the cache entirely, shared with the commit activity fetch
+    pub cache: Option<Arc<Cache>>,
+    /// Pause until the rate limit resets instead of failing
+    pub wait_on_rate_limit: bool,
+    /// How many levels of linked awesome lists to follow
+    pub depth: usize,
+}
+
+/// Progress reports sent by `Stats::fetch`.
+#[derive(Debug, Clone, Copy)]
+pub enum Progress {
+    Fetched {
+        current: usize,
+        total: usize,
+    },
+    /// Rate limited, every fetch is paused until the given time
+    Paused {
+        until: DateTime<Utc>,
+    },
 }
 
 impl Stats {
+    /// Fetch every entry, then follow the awesome lists among them down to `opts.depth`.
+    /// Repos found in a list remember it as their source.
+    /// Each repo and each list is visited once, which also stops cycles.
     pub async fn fetch(
-        oct: &Octocrab,
-        repos: Vec<(String, String)>,
-        progress: mpsc::Sender<(usize, usize)>,
-        min_stars: Option<u32>,
+        clients: &Clients,
+        entries: Vec<Entry>,
+        progress: mpsc::Sender<Progress>,
+        opts: &FetchOptions,
+    ) -> Result<Stats, Error> {
+        let mut seen: HashSet<String> = entries.iter().map(|e| e.id.key()).collect();
+        let mut listed: HashMap<String, Entry> =
+            entries.iter().map(|e| (e.id.key(), e.clone())).collect();
+        let mut stats = Self::fetch_level(clients, entries, progress.clone(), opts).await?;
+        Self::add_renamed(&mut listed, &stats.problems);
+
+        let throttle = &Throttle::new(progress.clone());
+        let mut read = HashSet::new();
+        let mut level = stats.repos.clone();
+        for _ in 0..opts.depth {
+            let lists: Vec<_> = level
+                .iter()
+                .filter(|repo| repo.is_list() && read.insert(repo.id.key()))
+                .map(|repo| repo.id.clone())
+                .collect();
+            let mut readmes: Vec<_> = stream::iter(lists.into_iter().enumerate())
+                .map(|(i, list)| async move {
+                    let readme =
+                        Self::throttled(opts, throttle, || github::readme(&clients.github, &list))
+                            .await;
+                    (i, list, readme)
+                })
+                .buffer_unordered(opts.jobs.max(1))
+                .collect()
+                .await;
+            // READMEs finish out of order, read them in the order the lists were found
+            readmes.sort_by_key(|(i, ..)| *i);
+
+            let mut found = Vec::new();
+            for (_, list, readme) in readmes {
+                let content = match readme {
+                    Ok(content) => content,
+                    Err(e @ Error::RateLimit { .. }) => return Err(e),
+                    Err(e) => {
+                        if let Some(entry) = listed.get(&list.key()) {
+                            stats.problems.push(Problem {
+                                entry: entry.clone(),
+                                kind: ProblemKind::Failed(format!(
+                                    "reading README: {}",
+                                    reason(&e)
+                                )),
+                            });
+                        }
+                        continue;
+                    }
+                };
+                for entry in parse_repos(&content) {
+                    if seen.insert(entry.id.key()) {
+                        found.push(Entry {
+                            sources: vec![list.to_string()],
+                            ..entry
+                        });
+                    }
+                }
+            }
+            if found.is_empty() {
+                break;
+            }
+            listed.extend(found.iter().map(|e| (e.id.key(), e.clone())));
+            let next = Self::fetch_level(clients, found, progress.clone(), opts).await?;
+            Self::add_renamed(&mut listed, &next.problems);
+            level = next.repos.clone();
+            stats.repos.extend(next.repos);
+            stats.problems.extend(next.problems);
+        }
+        Ok(stats)
+    }
+
+    /// Renamed repos come back under their new name, key them to the entry that asked for the old one.
+    fn add_renamed(listed: &mut HashMap<String, Entry>, problems: &[Problem]) {
+        for problem in problems {
+            if let ProblemKind::Renamed(id) = &problem.kind {
+                listed.insert(id.key(), problem.entry.clone());
+            }
+        }
+    }
+
+    async fn fetch_level(
+        clients: &Clients,
+        entries: Vec<Entry>,
+        progress: mpsc::Sender<Progress>,
+        opts: &FetchOptions,
     ) -> Result<Stats, Error> {
-        let total = repos.len();
+        let total = entries.len();
+        let throttle = &Throttle::new(progress.clone());
+        let mut slots: Vec<Option<Lookup>> = (0..total).map(|_| None).collect();
+
+        // Serve search results and fresh cache entries right away, only the rest hits the network.
+        let mut pending = Vec::new();
+        for (i, entry) in entries.iter().enumerate() {
+            if let Some(repo) = &entry.repo {
+                if let Some(cache) = &opts.cache {
+                    cache.refresh(&entry.id, repo);
+                }
+                slots[i] = Some(Ok(repo.clone()));
+                continue;
+            }
+            match opts.cache.as_ref().and_then(|c| c.fresh(&entry.id)) {
+                Some(repo) => slots[i] = Some(Ok(repo)),
+                None => pending.push((i, entry.id.clone())),
+            }
+        }
+
+        // Only GitHub's GraphQL API can answer for many repos at once.
+        let (github, others): (Vec<_>, Vec<_>) = pending
+            .iter()
+            .cloned()
+            .partition(|(_, id)| id.forge == Forge::GitHub);
+        let github_batch_size = match opts.backend {
+            Backend::Rest => 1,
+            Backend::GraphQl => graphql::BATCH_SIZE,
+        };
+        let batches: Vec<_> = github
+            .chunks(github_batch_size)
+            .chain(others.chunks(1))
+            .map(|batch| batch.iter().cloned().unzip::<_, _, Vec<_>, Vec<_>>())
+            .collect();
+        let mut fetches = stream::iter(batches)
+            .map(|(indices, batch)| async move {
+                (
+                    indices,
+                    Self::fetch_batch(clients, opts, throttle, &batch).await,
+                )
+            })
+            .buffer_unordered(opts.jobs.max(1));
+
+        // Requests finish out of order, keep the slot index to restore the input order.
+        let mut current = total - pending.len();
+        let _ = progress.send(Progress::Fetched { current, total }).await;
+        let mut result = Ok(());
+        while let Some((indices, stats)) = fetches.next().await {
+            let stats = match stats {
+                Ok(stats) => stats,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            };
+            current += stats.len();
+            for (i, stat) in indices.into_iter().zip(stats) {
+                slots[i] = Some(stat);
+            }
+            let _ = progress.send(Progress::Fetched { current, total }).await;
+        }
+
+        // Keep whatever was fetched, even if the run failed halfway.
+        if let Some(cache) = &opts.cache {
+            cache.save()?;
+        }
+        result?;
+
         let mut results = Vec::new();
-        for (i, (owner, repo)) in repos.iter().enumerate() {
-            if let Some(stat) = Repo::fetch(oct, owner, repo).await?
-                && min_stars.is_none_or(|min| stat.stars >= min)
-            {
-                results.push(stat);
+        let mut problems = Vec::new();
+        for (entry, stat) in entries.into_iter().zip(slots) {
+            let Some(stat) = stat else { continue };
+            match stat {
+                Ok(repo) => {
+                    let requested = entry.id.to_string();
+                    if !repo.id.to_string().eq_ignore_ascii_case(&requested) {
+                        let kind = ProblemKind::Renamed(repo.id.clone());
+                        problems.push(Problem {
+                            entry: entry.clone(),
+                            kind,
+                        });
+                    }
+                    let excluded = (opts.exclude_forks && repo.fork)
+                        || (opts.exclude_archived && repo.archived);
+                    if !excluded && opts.min_stars.is_none_or(|min| repo.stars >= min) {
+                        results.push(Repo {
+                            category: entry.category,
+                            sources: entry.sources,
+                            packages: entry.packages,
+                            path: entry.path,
+                            label: entry.label,
+                            annotation: entry.annotation,
+                            ..repo
+                        });
+                    }
+                }
+                Err(kind) => problems.push(Problem { entry, kind }),
+            }
+        }
+        Ok(Stats {
+            repos: results,
+            problems,
+        })
+    }
+
+    async fn fetch_batch(
+        clients: &Clients,
+        opts: &FetchOptions,
+        throttle: &Throttle,
+        batch: &[RepoId],
+    ) -> Result<Vec<Lookup>, Error> {
+        let cache = opts.cache.as_ref();
+        let oct = &clients.github;
+        match (batch[0].forge, opts.backend) {
+            (Forge::GitHub, Backend::Rest) => {
+                let mut stats = Vec::with_capacity(batch.len());
+                for id in batch {
+                    let cached = cache.and_then(|c| c.get(id));
+                    let etag = cached.as_ref().and_then(|entry| entry.etag.as_deref());
+                    let fetched =
+                        Self::throttled(opts, throttle, || github::fetch(oct, id, etag)).await;
+                    let stat = match fetched {
+                        Ok(Fetched::Found { repo, etag }) => {
+                            if let Some(cache) = cache {
+                                cache.insert(id, &repo, etag);
+                            }
+                            Ok(*repo)
+                        }
+                        Ok(Fetched::NotModified) => cache
+                            .and_then(|cache| cache.touch(id))
+                            .ok_or(ProblemKind::NotFound),
+                        Ok(Fetched::NotFound) => Err(ProblemKind::NotFound),
+                        Err(e @ Error::RateLimit { .. }) => return Err(e),
+                        Err(e) => Err(ProblemKind::Failed(reason(&e))),
+                    };
+                    stats.push(stat);
+                }
+                Ok(stats)
+            }
+            (Forge::GitHub, Backend::GraphQl) => {
+                let stats =
+                    match Self::throttled(opts, throttle, || graphql::fetch(oct, batch)).await {
+                        Ok(stats) => stats,
+                        Err(e @ Error::RateLimit { .. }) => return Err(e),
+                        Err(e) => vec![Err(ProblemKind::Failed(reason(&e))); batch.len()],
+                    };
+                if let Some(cache) = cache {
+                    // GraphQL has no ETag, stale entries are simply refetched
+                    for (id, stat) in batch.iter().zip(&stats) {
+                        if let Ok(repo) = stat {
+                            cache.refresh(id, repo);
+                        }
+                    }
+                }
+                Ok(stats)
+            }
+            _ => {
+                let mut stats = Vec::with_capacity(batch.len());
+                for id in batch {
+                    let stat = match forge::fetch(clients, id).await {
+                        Ok(Some(repo)) => {
+                            if let Some(cache) = cache {
+                                cache.insert(id, &repo, None);
+                            }
+                            Ok(repo)
+                        }
+                        Ok(None) => Err(ProblemKind::NotFound),
+                        Err(e) => Err(ProblemKind::Failed(reason(&e))),
+                    };
+                    stats.push(stat);
+                }
+                Ok(stats)
+            }
+        }
+    }
+
+    /// Run `request`, waiting out rate limits and retrying unless told to fail fast.
+    async fn throttled<T, F, Fut>(
+        opts: &FetchOptions,
+        throttle: &Throttle,
+        request: F,
+    ) -> Result<T, Error>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        loop {
+            throttle.wait().await;
+            match request().await {
+                Err(Error::RateLimit { reset: Some(until) }) if opts.wait_on_rate_limit => {
+                    throttle.pause_until(until).await;
+                }
+                result => return result,
             }
-            let _ = progress.send((i + 1, total)).await;
         }
-        Ok(Stats { repos: results })
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Repo {
-    pub owner: String,
-    pub name: String,
+    #[serde(flatten)]
+    pub id: RepoId,
     pub stars: u32,
     pub forks: u32,
     /// Age
@@ -43,59 +411,393 @@ pub struct Repo {
     pub description: Option<String>,
     pub topics: Vec<String>,
     pub homepage: Option<String>,
+    /// Section path in the input list, e.g. `["Web", "Frameworks"]`
+    #[serde(default)]
+    pub category: Vec<String>,
+    /// Inputs that mention the repo
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Packages published from the repo, e.g. crates
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// Local clone, when read from a directory
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Link text in the input list
+    #[serde(default)]
+    pub label: Option<String>,
+    /// The input list's own description of the repo
+    #[serde(default)]
+    pub annotation: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub fork: bool,
+    /// Full name of the repo this one was forked from
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub is_template: bool,
+    #[serde(default)]
+    pub disabled: bool,
+    /// `public`, `private`, `internal`, or `unlisted` on sourcehut
+    #[serde(default)]
+    pub visibility: Option<String>,
+    /// GitHub counts open pull requests too
+    #[serde(default)]
+    pub open_issues: u32,
+    /// People watching the repo for notifications, not stargazers
+    #[serde(default)]
+    pub watchers: u32,
+    /// Primary language
+    #[serde(default)]
+    pub language: Option<String>,
+    /// In kilobytes
+    #[serde(default)]
+    pub size: u32,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// `None` when the forge wasn't asked, e.g. for search results
+    #[serde(default)]
+    pub releases: Option<Releases>,
+    /// Commits per week over the last year, oldest first.
+    /// Fetched in the background after the rest, see `activity`, like `contributors`.
+    #[serde(default)]
+    pub commit_activity: Option<Vec<u32>>,
+    #[serde(default)]
+    pub contributors: Option<Contributors>,
+}
+
+/// Who writes the code, and how much of it depends on a few people.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Contributors {
+    /// `None` when there are more than the hundred fetched, and the forge wouldn't say how many
+    pub count: Option<u32>,
+    /// The fewest people who wrote half the commits, among the top hundred
+    pub bus_factor: u32,
+    /// The most active people and their commits, most first
+    pub top: Vec<(String, u32)>,
+}
+
+impl Contributors {
+    /// How many of the top contributors are kept for the detail view.
+    const TOP: usize = 5;
+
+    /// From each contributor's commit count, in any order.
+    pub fn new(mut commits: Vec<(String, u32)>, count: Option<u32>) -> Self {
+        commits.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+        let total: u64 = commits.iter().map(|(_, n)| u64::from(*n)).sum();
+        let (mut covered, mut bus_factor) = (0, 0);
+        for (_, n) in &commits {
+            if covered * 2 >= total {
+                break;
+            }
+            covered += u64::from(*n);
+            bus_factor += 1;
+        }
+        commits.truncate(Self::TOP);
+        Self {
+            count,
+            bus_factor,
+            top: commits,
+        }
+    }
+
+    /// The count, or `100+` when it's unknown.
+    pub fn count_label(&self) -> String {
+        self.count
+            .map_or_else(|| "100+".to_string(), Repo::prettify_num)
+    }
+}
+
+/// How often a repo cuts releases.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Releases {
+    /// The newest release, `None` if there are none
+    pub latest: Option<DateTime<Utc>>,
+    /// Releases in the last 12 months
+    pub last_year: u32,
+}
+
+impl Releases {
+    /// From the publication dates of the newest releases, drafts have none.
+    pub fn new(published: impl IntoIterator<Item = DateTime<Utc>>) -> Self {
+        let year_ago = Utc::now() - chrono::Duration::days(365);
+        let mut releases = Self::default();
+        for date in published {
+            releases.latest = releases.latest.max(Some(date));
+            if date > year_ago {
+                releases.last_year += 1;
+            }
+        }
+        releases
+    }
+}
+
+/// Columns left out of the table unless asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Issues,
+    Watchers,
+    Language,
+    Size,
+    Branch,
+    /// Published packages, shown by itself when some repo has one
+    Package,
+    /// Local clone, shown by itself when some repo has one
+    Path,
+}
+
+impl Column {
+    /// The ones `c` toggles in the UI.
+    pub const ALL: [Column; 5] = [
+        Column::Issues,
+        Column::Watchers,
+        Column::Language,
+        Column::Size,
+        Column::Branch,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Issues => "Issues",
+            Column::Watchers => "Watchers",
+            Column::Language => "Language",
+            Column::Size => "Size",
+            Column::Branch => "Branch",
+            Column::Package => "Package",
+            Column::Path => "Path",
+        }
+    }
+
+    fn value(self, repo: &Repo) -> String {
+        match self {
+            Column::Issues => Repo::prettify_num(repo.open_issues),
+            Column::Watchers => Repo::prettify_num(repo.watchers),
+            Column::Language => repo.language.clone().unwrap_or_default(),
+            Column::Size => Repo::prettify_size(repo.size),
+            Column::Branch => repo.default_branch.clone().unwrap_or_default(),
+            Column::Package => repo.packages.join(", "),
+            Column::Path => repo.local_path(),
+        }
+    }
+
+    /// The `HEADER` column this one goes after.
+    /// Most go after the dates, before where the repo came from.
+    fn after(self) -> &'static str {
+        match self {
+            Column::Package => "Flags",
+            Column::Path => "Source",
+            _ => "Bus factor",
+        }
+    }
+
+    /// Add the columns only some inputs fill, when one of the `repos` has them.
+    pub fn add_present(columns: &mut Vec<Column>, repos: &[Repo]) {
+        let has_package = repos.iter().any(|r| !r.packages.is_empty());
+        let has_path = repos.iter().any(|r| r.path.is_some());
+        let present = [(Column::Package, has_package), (Column::Path, has_path)];
+        for (column, present) in present {
+            if present && !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+    }
 }
 
 impl Repo {
-    pub fn ref_array(&self) -> [String; 6] {
+    pub const HEADER: [&str; 14] = [
+        "Name",
+        "Flags",
+        "Forge",
+        "Stars",
+        "Forks",
+        "License",
+        "Age",
+        "Updated",
+        "Released",
+        "Activity",
+        "Contributors",
+        "Bus factor",
+        "Source",
+        "Category",
+    ];
+
+    pub fn ref_array(&self) -> [String; 14] {
+        let (contributors, bus_factor) = match &self.contributors {
+            Some(c) => (c.count_label(), c.bus_factor.to_string()),
+            None => Default::default(),
+        };
         [
-            self.name.clone(),                            // Name
+            self.id.name.clone(),                         // Name
+            self.flags(),                                 // Flags
+            self.id.forge.to_string(),                    // Forge
             Self::prettify_num(self.stars),               // Stars
             Self::prettify_num(self.forks),               // Forks
             self.license.clone(),                         // License
             HumanTime::from(self.created_at).to_string(), // Age
             HumanTime::from(self.pushed_at).to_string(),  // Updated
+            self.released(),                              // Released
+            self.sparkline(),                             // Activity
+            contributors,                                 // Contributors
+            bus_factor,                                   // Bus factor
+            self.source(),                                // Source
+            self.category(),                              // Category
         ]
     }
 
-    pub async fn fetch(oct: &Octocrab, owner: &str, name: &str) -> Result<Option<Repo>, Error> {
-        let info = match oct.repos(owner, name).get().await {
-            Ok(info) => info,
-            Err(octocrab::Error::GitHub { source, .. }) if source.status_code == 404 => {
-                return Ok(None); // skip silently
-            }
-            Err(octocrab::Error::GitHub { source, .. }) if source.status_code == 403 => {
-                return Err(Error::RateLimit);
-            }
-            Err(e) => return Err(Error::GitHub(e)),
+    /// `HEADER` with the optional `columns` added.
+    pub fn header(columns: &[Column]) -> Vec<&'static str> {
+        Self::HEADER
+            .iter()
+            .flat_map(|&title| {
+                let added = columns.iter().filter(move |c| c.after() == title);
+                std::iter::once(title).chain(added.map(|c| c.title()))
+            })
+            .collect()
+    }
+
+    /// `ref_array` with the optional `columns` added, matching `header`.
+    pub fn row(&self, columns: &[Column]) -> Vec<String> {
+        Self::HEADER
+            .iter()
+            .zip(self.ref_array())
+            .flat_map(|(&title, value)| {
+                let added = columns.iter().filter(move |c| c.after() == title);
+                std::iter::once(value).chain(added.map(|c| c.value(self)))
+            })
+            .collect()
+    }
+
+    /// One letter per notable state: Archived, Disabled, Fork, Template, Private.
+    pub fn flags(&self) -> String {
+        let private = self.visibility.as_deref().is_some_and(|v| v != "public");
+        [
+            (self.archived, 'A'),
+            (self.disabled, 'D'),
+            (self.fork, 'F'),
+            (self.is_template, 'T'),
+            (private, 'P'),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| *flag)
+        .collect()
+    }
+
+    /// Spelled out for the detail view, e.g. `archived, fork of o/r`.
+    pub fn status(&self) -> String {
+        let mut status = Vec::new();
+        if self.archived {
+            status.push("archived".to_string());
+        }
+        if self.disabled {
+            status.push("disabled".to_string());
+        }
+        if self.fork {
+            status.push(match &self.parent {
+                Some(parent) => format!("fork of {parent}"),
+                None => "fork".to_string(),
+            });
+        }
+        if self.is_template {
+            status.push("template".to_string());
+        }
+        if let Some(visibility) = &self.visibility {
+            status.push(visibility.clone());
+        }
+        status.join(", ")
+    }
+
+    /// Age of the latest release and how many came out in the last year, e.g. `3 months ago (4/yr)`.
+    pub fn released(&self) -> String {
+        match &self.releases {
+            None => String::new(),
+            Some(Releases { latest: None, .. }) => "Never".to_string(),
+            Some(Releases {
+                latest: Some(latest),
+                last_year,
+            }) => format!("{} ({last_year}/yr)", HumanTime::from(*latest)),
+        }
+    }
+
+    /// Commits in the last 13 weeks, unlike `pushed_at` a single bot commit barely moves it.
+    pub fn recent_commits(&self) -> Option<u32> {
+        let weeks = self.commit_activity.as_ref()?;
+        Some(weeks.iter().rev().take(13).sum())
+    }
+
+    /// The year of commit activity, two weeks per character.
+    pub fn sparkline(&self) -> String {
+        const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let Some(weeks) = &self.commit_activity else {
+            return String::new();
         };
-        let stars = info.stargazers_count.unwrap_or(0);
-        let forks = info.forks_count.unwrap_or(0);
-        let license = info
-            .license
-            .as_ref()
-            .map(|l| l.key.clone())
-            .unwrap_or_else(|| "None".to_string());
-        let age = info.created_at.unwrap();
-        let last_push = info.pushed_at.unwrap();
-        let description = info.description;
-        let topics = info.topics.unwrap_or_default();
-        let homepage = info.homepage;
-
-        Ok(Some(Repo {
-            owner: owner.to_string(),
-            name: name.to_string(),
-            stars,
-            forks,
-            license,
-            created_at: age,
-            pushed_at: last_push,
-            description,
-            topics,
-            homepage,
-        }))
-    }
-
-    fn prettify_num(stars: u32) -> String {
+        let pairs: Vec<u32> = weeks.chunks(2).map(|pair| pair.iter().sum()).collect();
+        let max = pairs.iter().copied().max().unwrap_or(0).max(1);
+        pairs
+            .iter()
+            .map(|&n| match n {
+                0 => BARS[0],
+                // Any commit at all shows, the busiest fortnight is full height
+                n => BARS[1 + (n * 7 / max) as usize],
+            })
+            .collect()
+    }
+
+    /// Words for states the filter can match, e.g. `no releases`.
+    pub fn keywords(&self) -> Vec<&'static str> {
+        let mut keywords = Vec::new();
+        if self.releases.as_ref().is_some_and(|r| r.latest.is_none()) {
+            keywords.push("no releases");
+        }
+        keywords
+    }
+
+    /// Awesome lists link to more repos, see `FetchOptions::depth`.
+    /// Named `awesome` or `awesome-*`, or tagged as one; `awesomewm` and the like are not lists.
+    pub fn is_list(&self) -> bool {
+        let name = self.id.name.to_lowercase();
+        self.id.forge == Forge::GitHub
+            && (name == "awesome"
+                || name.starts_with("awesome-")
+                || self
+                    .topics
+                    .iter()
+                    .any(|t| t == "awesome" || t == "awesome-list"))
+    }
+
+    /// Section path joined for display, e.g. `Web > Frameworks`.
+    pub fn category(&self) -> String {
+        self.category.join(" > ")
+    }
+
+    pub fn source(&self) -> String {
+        self.sources.join(", ")
+    }
+
+    /// Local clone, with the home directory shortened to `~`.
+    pub fn local_path(&self) -> String {
+        let Some(path) = &self.path else {
+            return String::new();
+        };
+        match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok()) {
+            Some(rest) => format!("~/{}", rest.display()),
+            None => path.display().to_string(),
+        }
+    }
+
+    fn prettify_size(kb: u32) -> String {
+        if kb >= 1_000_000 {
+            format!("{:.1} GB", kb as f32 / 1_000_000.0)
+        } else if kb >= 1_000 {
+            format!("{:.1} MB", kb as f32 / 1_000.0)
+        } else {
+            format!("{kb} KB")
+        }
+    }
+
+    pub fn prettify_num(stars: u32) -> String {
         if stars >= 1_000_000 {
             format!("{:.1}M", stars as f32 / 1_000_000.0)
         } else if stars >= 1_000 {
@@ -0,0 +1,66 @@
+use std::sync::Mutex;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use http::HeaderMap;
+use tokio::sync::mpsc;
+
+use crate::stats::Progress;
+
+/// Shared pause gate, so every concurrent fetch waits out the same rate limit
+/// instead of hammering GitHub while it is exhausted.
+pub struct Throttle {
+    resume_at: Mutex<Option<DateTime<Utc>>>,
+    progress: mpsc::Sender<Progress>,
+}
+
+impl Throttle {
+    pub fn new(progress: mpsc::Sender<Progress>) -> Self {
+        Self {
+            resume_at: Mutex::new(None),
+            progress,
+        }
+    }
+
+    /// Sleep until the current pause, if any, is over.
+    pub async fn wait(&self) {
+        let resume_at = *self.resume_at.lock().unwrap();
+        if let Some(wait) = resume_at.and_then(|at| (at - Utc::now()).to_std().ok()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Pause every fetch until `until`, and let the loading screen know.
+    pub async fn pause_until(&self, until: DateTime<Utc>) {
+        let extended = {
+            let mut resume_at = self.resume_at.lock().unwrap();
+            let extended = resume_at.is_none_or(|at| until > at);
+            if extended {
+                *resume_at = Some(until);
+            }
+            extended
+        };
+        // Concurrent fetches hit the same limit, only report it once
+        if extended {
+            let _ = self.progress.send(Progress::Paused { until }).await;
+        }
+    }
+}
+
+/// How long to wait out a secondary limit that doesn't send `retry-after`, as GitHub documents.
+pub const SECONDARY_LIMIT_WAIT: TimeDelta = TimeDelta::seconds(60);
+
+/// When a rate limited request may be retried, read from the response headers.
+/// Secondary limits send `retry-after`, the primary limit sends `x-ratelimit-reset`.
+pub fn reset_time(headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+
+    if let Some(secs) = header("retry-after") {
+        return Some(Utc::now() + TimeDelta::seconds(secs));
+    }
+    if header("x-ratelimit-remaining") == Some(0) {
+        // Give GitHub a moment past the advertised reset
+        return DateTime::from_timestamp(header("x-ratelimit-reset")?, 0)
+            .map(|reset| reset + TimeDelta::seconds(1));
+    }
+    None
+}
@@ -6,6 +6,9 @@ pub struct AppColor {
     pub normal_row_color: Color,
     pub alt_row_color: Color,
     pub footer_border_color: Color,
+    pub group_fg: Color,
+    pub group_bg: Color,
+    pub inactive_fg: Color,
 }
 
 impl Default for AppColor {
@@ -23,6 +26,9 @@ impl AppColor {
             normal_row_color: tailwind::SLATE.c950,
             alt_row_color: tailwind::SLATE.c900,
             footer_border_color: tailwind::NEUTRAL.c600,
+            group_fg: tailwind::VIOLET.c300,
+            group_bg: tailwind::SLATE.c800,
+            inactive_fg: tailwind::SLATE.c500,
         }
     }
 }
@@ -1,19 +1,22 @@
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
 use ratatui::layout::{Constraint, Layout, Margin, Rect};
-use ratatui::style::{self, Style};
+use ratatui::style::{self, Modifier, Style};
 use ratatui::text::Text;
 use ratatui::widgets::{
-    Block, BorderType, Cell, Clear, HighlightSpacing, Paragraph, Row, Scrollbar,
-    ScrollbarOrientation, Table,
+    Bar, BarChart, Block, BorderType, Cell, Clear, HighlightSpacing, Paragraph, Row, Scrollbar,
+    ScrollbarOrientation, ScrollbarState, Table,
 };
 use ratatui::{DefaultTerminal, Frame};
 use style::palette::tailwind;
 
 use crate::Error;
-use crate::ui::App;
+use crate::stats::{Releases, Repo};
+use crate::ui::{App, Line};
 
 const INFO_TEXT: [&str; 2] = [
-    "Sort by: (1) Name | (2) Stars | (3) Forks | (4) Age | (5) Updated",
-    "(o) Open | (y) Copy | (e) Export | (d) Detail | (?) Help",
+    "Sort by: (1) Name | (2) Stars | (3) Forks | (4) Age | (5) Updated | (6) Issues | (7) Watchers | (8) Language | (9) Size | (0) Branch | (a) Commits 90d",
+    "(o) Open | (E) Edit clone | (y) Copy | (e) Export | (d) Detail | (g) Group | (c) Columns | (p) Problems | (?) Help",
 ];
 
 impl App {
@@ -32,6 +35,10 @@ impl App {
         if self.show_detail {
             self.render_detail(frame);
         }
+
+        if self.show_problems {
+            self.render_problems(frame);
+        }
     }
 
     fn render_table(&mut self, frame: &mut Frame, area: Rect) {
@@ -40,50 +47,105 @@ impl App {
             .bg(self.colors.selected_row_style_fg)
             .fg(self.colors.row_fg);
 
-        let header = ["Name", "Stars", "Forks", "License", "Age", "Updated"]
-            .into_iter()
+        let columns = Repo::header(&self.columns);
+        let header = columns
+            .iter()
+            .copied()
             .map(Cell::from)
             .collect::<Row>()
             .style(header_style)
             .height(1);
-        let rows = self.filtered.iter().enumerate().map(|(i, &idx)| {
-            let data = &self.items[idx];
+        let rows = self.lines.iter().enumerate().map(|(i, line)| {
             let color = match i % 2 {
                 0 => self.colors.normal_row_color,
                 _ => self.colors.alt_row_color,
             };
-            let item = data.ref_array();
-            let cells = item.into_iter().enumerate().map(|(j, content)| {
-                let cell = Cell::from(Text::from(content.to_string()));
-                match j {
-                    // stars
-                    1 => cell.style(Style::new().fg(Self::popularity_color(data.stars))),
-                    // fork
-                    2 => cell.style(Style::new().fg(Self::popularity_color(data.stars))),
-                    // Age
-                    5 => cell.style(Style::new().fg(Self::abandoned_color(data.pushed_at))),
+            let idx = match line {
+                Line::Repo(idx) => *idx,
+                Line::Group {
+                    category,
+                    count,
+                    collapsed,
+                } => {
+                    let marker = if *collapsed { '▸' } else { '▾' };
+                    let label = group_label(category);
+                    return Row::new([Cell::from(format!("{marker} {label} ({count})"))])
+                        .style(
+                            Style::new()
+                                .fg(self.colors.group_fg)
+                                .bg(self.colors.group_bg)
+                                .add_modifier(Modifier::BOLD),
+                        )
+                        .height(1);
+                }
+            };
+            let data = &self.items[idx];
+            let item = data.row(&self.columns);
+            let cells = columns.iter().zip(item).map(|(column, content)| {
+                let cell = Cell::from(Text::from(content));
+                match *column {
+                    "Stars" | "Forks" => {
+                        cell.style(Style::new().fg(Self::popularity_color(data.stars)))
+                    }
+                    "Updated" => cell.style(Style::new().fg(Self::abandoned_color(data.pushed_at))),
+                    "Contributors" | "Bus factor" => match &data.contributors {
+                        // Empty repos have nobody to blame
+                        Some(c) if !c.top.is_empty() => {
+                            cell.style(Style::new().fg(Self::bus_factor_color(c.bus_factor)))
+                        }
+                        _ => cell,
+                    },
+                    "Released" => match data.releases.as_ref().and_then(|r| r.latest) {
+                        Some(latest) => cell.style(Style::new().fg(Self::abandoned_color(latest))),
+                        None => cell,
+                    },
                     _ => cell,
                 }
             });
-            Row::new(cells)
-                .style(Style::new().fg(self.colors.row_fg).bg(color))
-                .height(1)
+            // Archived and disabled repos are dimmed, forks in italics
+            let mut style = Style::new().fg(self.colors.row_fg).bg(color);
+            if data.archived || data.disabled {
+                style = style.fg(self.colors.inactive_fg);
+            }
+            if data.fork {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
+            Row::new(cells).style(style).height(1)
         });
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Length(15), // Name
-                Constraint::Length(8),  // Stars
-                Constraint::Length(8),  // Forks
-                Constraint::Length(15), // License
-                Constraint::Length(15), // Age
-                Constraint::Min(15),    // Updated
-            ],
-        )
-        .header(header)
-        .highlight_symbol("  ")
-        .highlight_spacing(HighlightSpacing::Always)
-        .row_highlight_style(selected_row_style);
+        // Group headers only have the name column, widen it so they aren't cut short
+        let name_width = match self.grouped {
+            true => self
+                .lines
+                .iter()
+                .filter_map(|line| match line {
+                    Line::Group {
+                        category, count, ..
+                    } => Some(group_label(category).chars().count() + count.to_string().len() + 5),
+                    Line::Repo(_) => None,
+                })
+                .max()
+                .unwrap_or(0)
+                .clamp(15, 40),
+            false => 15,
+        };
+        let widths = columns.iter().map(|column| match *column {
+            "Name" => Constraint::Length(name_width as u16),
+            "Flags" | "Issues" => Constraint::Length(6),
+            "Bus factor" => Constraint::Length(10),
+            "Contributors" => Constraint::Length(12),
+            "Forge" | "Language" | "Size" | "Watchers" => Constraint::Length(10),
+            "Stars" | "Forks" => Constraint::Length(8),
+            "Source" | "Path" | "Released" => Constraint::Length(20),
+            // two weeks per character
+            "Activity" => Constraint::Length(26),
+            "Category" => Constraint::Min(15),
+            _ => Constraint::Length(15),
+        });
+        let table = Table::new(rows, widths)
+            .header(header)
+            .highlight_symbol("  ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .row_highlight_style(selected_row_style);
         frame.render_stateful_widget(table, area, &mut self.state);
     }
 
@@ -132,12 +194,20 @@ impl App {
         let text = [
             "  Keybindings",
             "  ──────────────────────────",
-            "  1-5    Sort by column",
-            "  /      Filter repos",
+            "  1-0    Sort by column",
+            "  a      Sort by commits in the last 90 days",
+            "  c      Show/hide the extra columns",
+            "  /      Filter by name, list description or `no releases`, source:word by input",
             "  o      Open in browser",
+            "  E      Open local clone in $EDITOR",
             "  e      Export to markdown",
             "  d      Show detail",
+            "  p      Show problems (j/k to scroll)",
+            "  g      Group by category",
+            "  Space  Collapse/expand group",
+            "  z      Collapse/expand all groups",
             "  y      Yank URL to clipboard",
+            "  Flags  A archived, D disabled, F fork, T template, P private",
             "  j/↓    Move down",
             "  k/↑    Move up",
             "  ?      Toggle this help",
@@ -157,10 +227,10 @@ impl App {
     }
 
     pub fn render_detail(&self, frame: &mut Frame) {
-        // The selection must be active
-        // No need to check for None
-        let i = self.state.selected().unwrap();
-        let &idx = self.filtered.get(i).unwrap();
+        // Nothing to show on a category header
+        let Some(idx) = self.selected_repo() else {
+            return;
+        };
 
         let repo = &self.items[idx];
         let area = frame.area();
@@ -179,22 +249,143 @@ impl App {
             repo.topics.join(", ")
         };
 
+        // How the input list described it, e.g. `[foo](...) - Fast thing`
+        let listed = match (&repo.label, &repo.annotation) {
+            (Some(label), Some(annotation)) => format!("{label} - {annotation}"),
+            (Some(text), None) | (None, Some(text)) => text.clone(),
+            (None, None) => "None".to_string(),
+        };
+
+        let status = match repo.status() {
+            s if s.is_empty() => "Active".to_string(),
+            s => s,
+        };
+
+        let releases = match &repo.releases {
+            None => "Unknown".to_string(),
+            Some(Releases { latest: None, .. }) => "None".to_string(),
+            Some(Releases {
+                latest: Some(latest),
+                last_year,
+            }) => format!(
+                "latest {}, {last_year} in the last 12 months",
+                HumanTime::from(*latest)
+            ),
+        };
+
+        // e.g. `alice (1.2k), bob (300)`
+        let contributors = match &repo.contributors {
+            None => "Unknown".to_string(),
+            Some(c) if c.top.is_empty() => "None".to_string(),
+            Some(c) => format!(
+                "{} of {}, half the commits by {}",
+                c.top
+                    .iter()
+                    .map(|(name, commits)| format!("{name} ({})", Repo::prettify_num(*commits)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                c.count_label(),
+                match c.bus_factor {
+                    1 => "one person".to_string(),
+                    n => format!("{n} people"),
+                }
+            ),
+        };
+
         let text = format!(
-            "  Description: {}\n\n  Homepage: {}\n\n  Topics: {}",
-            description, homepage, topics
+            "  Description: {}\n\n  Status: {}\n\n  Releases: {}\n\n  Top contributors: {}\n\n  Listed as: {}\n\n  Homepage: {}\n\n  Topics: {}",
+            description, status, releases, contributors, listed, homepage, topics
         );
 
+        let block = Block::bordered()
+            .title(format!(" {} ", repo.id.name))
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().fg(tailwind::VIOLET.c400));
+        let inner = block.inner(popup);
+        frame.render_widget(Clear, popup);
+        frame.render_widget(block, popup);
+
+        // The chart takes the bottom of the popup, when there is activity to show
+        let weeks = repo.commit_activity.as_deref().unwrap_or_default();
+        let chart_height = if weeks.is_empty() { 0 } else { 8 };
+        let layout = Layout::vertical([Constraint::Min(0), Constraint::Length(chart_height)]);
+        let [text_area, chart_area] = inner.layout(&layout);
+
+        let paragraph = Paragraph::new(text).wrap(ratatui::widgets::Wrap { trim: false });
+        frame.render_widget(paragraph, text_area);
+
+        if !weeks.is_empty() {
+            let bars: Vec<_> = weeks
+                .iter()
+                .map(|&n| Bar::new(u64::from(n)).text_value(""))
+                .collect();
+            let chart = BarChart::new(bars)
+                .bar_width(1)
+                .bar_gap(0)
+                .bar_style(Style::new().fg(tailwind::VIOLET.c400))
+                .block(Block::new().title(format!(
+                    "  Commits per week, last year ({} in the last 90 days)",
+                    repo.recent_commits().unwrap_or_default()
+                )));
+            frame.render_widget(chart, chart_area);
+        }
+    }
+
+    fn render_problems(&self, frame: &mut Frame) {
+        let area = frame.area();
+        let popup = Rect {
+            x: area.width / 8,
+            y: area.height / 4,
+            width: area.width * 3 / 4,
+            height: area.height / 2,
+        };
+
+        let text = if self.problems.is_empty() {
+            "  Every repo was found".to_string()
+        } else {
+            self.problems
+                .iter()
+                .map(|p| format!("  {p}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
         let block = Paragraph::new(text)
             .wrap(ratatui::widgets::Wrap { trim: false })
+            .scroll((self.problems_scroll, 0))
             .block(
                 Block::bordered()
-                    .title(format!(" {} ", repo.name))
+                    .title(format!(" Problems ({}) ", self.problems.len()))
                     .border_type(BorderType::Rounded)
                     .border_style(Style::new().fg(tailwind::VIOLET.c400)),
             );
 
         frame.render_widget(Clear, popup);
         frame.render_widget(block, popup);
+
+        let mut scroll_state =
+            ScrollbarState::new(self.problems.len()).position(usize::from(self.problems_scroll));
+        frame.render_stateful_widget(
+            Scrollbar::default()
+                .style(Style::new().fg(tailwind::VIOLET.c400))
+                .orientation(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            popup.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut scroll_state,
+        );
+    }
+}
+
+/// Repos from outside any section are grouped together.
+fn group_label(category: &str) -> &str {
+    if category.is_empty() {
+        "Uncategorized"
+    } else {
+        category
     }
 }
 
@@ -202,7 +393,21 @@ pub fn draw_loading(
     terminal: &mut DefaultTerminal,
     current: usize,
     total: usize,
+    paused_until: Option<DateTime<Utc>>,
 ) -> Result<(), Error> {
+    let text = match paused_until {
+        Some(until) => {
+            let secs = (until - Utc::now()).num_seconds().max(0);
+            format!(
+                "Rate limited, resuming in {}m {:02}s ({}/{})...",
+                secs / 60,
+                secs % 60,
+                current,
+                total
+            )
+        }
+        None => format!("Fetching {}/{}...", current, total),
+    };
     terminal.draw(|f| {
         let area = f.area();
         let vertical = Layout::vertical([
@@ -211,10 +416,7 @@ pub fn draw_loading(
             Constraint::Fill(1),
         ])
         .split(area);
-        f.render_widget(
-            Paragraph::new(format!("Fetching {}/{}...", current, total)).centered(),
-            vertical[1],
-        )
+        f.render_widget(Paragraph::new(text).centered(), vertical[1])
     })?;
     Ok(())
 }
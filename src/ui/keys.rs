@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crossterm::event::{self, KeyCode};
 
 use crate::Error;
@@ -5,17 +7,40 @@ use crate::ui::{App, SortBy};
 
 impl App {
     pub fn handle_key(&mut self) -> Result<bool, Error> {
+        // Come back now and then to redraw what arrived in the background
+        if !event::poll(Duration::from_millis(250))? {
+            return Ok(false);
+        }
         if let Some(key) = event::read()?.as_key_press_event() {
+            // While typing a filter every letter belongs to it, `source:` included
+            if self.filtering {
+                self.handle_filter_key(key.code);
+                return Ok(false);
+            }
             match key.code {
+                // The problems popup takes the row keys while it's open
+                KeyCode::Char('j') | KeyCode::Down if self.show_problems => {
+                    let last = self.problems.len().saturating_sub(1);
+                    let last = u16::try_from(last).unwrap_or(u16::MAX);
+                    self.problems_scroll = self.problems_scroll.saturating_add(1).min(last);
+                }
+                KeyCode::Char('k') | KeyCode::Up if self.show_problems => {
+                    self.problems_scroll = self.problems_scroll.saturating_sub(1);
+                }
                 KeyCode::Char('j') | KeyCode::Down => self.next_row(),
                 KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
                 KeyCode::Char('o') => {
-                    if let Some(i) = self.state.selected() {
+                    if let Some(i) = self.selected_repo() {
                         let _ = open::that(self.repo_url(i));
                     }
                 }
+                KeyCode::Char('E') if !self.filtering => {
+                    if let Some(i) = self.selected_repo() {
+                        self.edit_path = self.items[i].path.clone();
+                    }
+                }
                 KeyCode::Char('y') => {
-                    if let Some(i) = self.state.selected() {
+                    if let Some(i) = self.selected_repo() {
                         let url = self.repo_url(i);
                         if let Some(clipboard) = &mut self.clipboard {
                             let _ = clipboard.set_text(url);
@@ -28,31 +53,32 @@ impl App {
                 KeyCode::Char('d') => {
                     self.show_detail = !self.show_detail;
                 }
+                KeyCode::Char('g') if !self.filtering => {
+                    self.toggle_grouping();
+                }
+                KeyCode::Char(' ') if !self.filtering => {
+                    self.toggle_collapse();
+                }
+                KeyCode::Char('z') if !self.filtering => {
+                    self.toggle_collapse_all();
+                }
+                KeyCode::Char('c') if !self.filtering => {
+                    self.toggle_columns();
+                }
+                KeyCode::Char('a') if !self.filtering => {
+                    self.sort_by = SortBy::Commits;
+                    self.sort();
+                }
+                KeyCode::Char('p') => {
+                    self.show_problems = !self.show_problems;
+                    self.problems_scroll = 0;
+                }
                 KeyCode::Char('/') => {
                     self.filtering = true;
                     self.filter = Some(String::new());
                 }
                 KeyCode::Char('q') | KeyCode::Esc => {
-                    if self.filtering {
-                        self.filtering = false;
-                        self.filter = None;
-                        self.apply_filter();
-                    } else {
-                        return Ok(true); // signal quit
-                    }
-                }
-                KeyCode::Char(c) if self.filtering => {
-                    self.filter.get_or_insert_default().push(c);
-                    self.apply_filter();
-                }
-                KeyCode::Backspace if self.filtering => {
-                    if let Some(f) = &mut self.filter {
-                        f.pop();
-                        self.apply_filter();
-                    }
-                }
-                KeyCode::Enter if self.filtering => {
-                    self.filtering = false;
+                    return Ok(true); // signal quit
                 }
                 KeyCode::Char('1') => {
                     self.sort_by = SortBy::Name;
@@ -74,6 +100,26 @@ impl App {
                     self.sort_by = SortBy::Updated;
                     self.sort();
                 }
+                KeyCode::Char('6') => {
+                    self.sort_by = SortBy::Issues;
+                    self.sort();
+                }
+                KeyCode::Char('7') => {
+                    self.sort_by = SortBy::Watchers;
+                    self.sort();
+                }
+                KeyCode::Char('8') => {
+                    self.sort_by = SortBy::Language;
+                    self.sort();
+                }
+                KeyCode::Char('9') => {
+                    self.sort_by = SortBy::Size;
+                    self.sort();
+                }
+                KeyCode::Char('0') => {
+                    self.sort_by = SortBy::Branch;
+                    self.sort();
+                }
                 KeyCode::Char('?') => {
                     self.show_help = !self.show_help;
                 }
@@ -82,4 +128,30 @@ impl App {
         }
         Ok(false)
     }
+
+    fn handle_filter_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.filtering = false;
+                self.filter = None;
+                self.apply_filter();
+            }
+            KeyCode::Enter => {
+                self.filtering = false;
+            }
+            KeyCode::Backspace => {
+                if let Some(f) = &mut self.filter {
+                    f.pop();
+                    self.apply_filter();
+                }
+            }
+            KeyCode::Char(c) => {
+                self.filter.get_or_insert_default().push(c);
+                self.apply_filter();
+            }
+            KeyCode::Down => self.next_row(),
+            KeyCode::Up => self.previous_row(),
+            _ => {}
+        }
+    }
 }
@@ -2,79 +2,175 @@ pub mod colors;
 pub mod draw;
 pub mod keys;
 
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
 use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use ratatui::DefaultTerminal;
 use ratatui::style::{self, Color};
 use ratatui::widgets::{Paragraph, ScrollbarState, TableState};
 use style::palette::tailwind;
+use tokio::sync::mpsc;
 
-use crate::Error;
-use crate::stats::{Repo, Stats};
+use crate::activity;
+use crate::input::editor;
+use crate::stats::{Column, Problem, Repo, Stats};
 use crate::ui::colors::AppColor;
+use crate::{Error, output};
 
 const ITEM_HEIGHT: usize = 1;
 
-#[derive(Clone, Copy)]
-enum SortBy {
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SortBy {
     Name,
     Stars,
     Forks,
     Created,
     Updated,
+    Issues,
+    Watchers,
+    Language,
+    Size,
+    Branch,
+    /// Commits in the last 90 days
+    Commits,
+}
+
+impl SortBy {
+    pub fn sort(self, repos: &mut [Repo]) {
+        match self {
+            SortBy::Name => repos.sort_by(|a, b| a.id.name.cmp(&b.id.name)),
+            SortBy::Stars => repos.sort_by_key(|r| Reverse(r.stars)),
+            SortBy::Forks => repos.sort_by_key(|r| Reverse(r.forks)),
+            SortBy::Created => repos.sort_by_key(|r| r.created_at), // oldest first
+            SortBy::Updated => repos.sort_by_key(|r| Reverse(r.pushed_at)), // most recent first
+            SortBy::Issues => repos.sort_by_key(|r| Reverse(r.open_issues)),
+            SortBy::Watchers => repos.sort_by_key(|r| Reverse(r.watchers)),
+            // unknown last
+            SortBy::Language => repos.sort_by_key(|r| (r.language.is_none(), r.language.clone())),
+            SortBy::Size => repos.sort_by_key(|r| Reverse(r.size)),
+            SortBy::Branch => {
+                repos.sort_by_key(|r| (r.default_branch.is_none(), r.default_branch.clone()))
+            }
+            // unknown last
+            SortBy::Commits => repos.sort_by_key(|r| Reverse(r.recent_commits())),
+        }
+    }
+
+    /// The optional column holding the sorted value, if it is one.
+    fn column(self) -> Option<Column> {
+        match self {
+            SortBy::Issues => Some(Column::Issues),
+            SortBy::Watchers => Some(Column::Watchers),
+            SortBy::Language => Some(Column::Language),
+            SortBy::Size => Some(Column::Size),
+            SortBy::Branch => Some(Column::Branch),
+            _ => None,
+        }
+    }
+}
+
+/// A line of the table, either a category header or a repo.
+enum Line {
+    Group {
+        category: String,
+        count: usize,
+        collapsed: bool,
+    },
+    Repo(usize), // index into items
 }
 
 pub struct App {
     state: TableState,
     items: Vec<Repo>,
+    problems: Vec<Problem>,
     sort_by: SortBy,
+    /// Optional columns shown in the table
+    columns: Vec<Column>,
     scroll_state: ScrollbarState,
     colors: AppColor,
     // filter
     filtered: Vec<usize>,   // indices into items
     filter: Option<String>, // None = no filter
     filtering: bool,        // true = user is typing
+    // grouping
+    lines: Vec<Line>,
+    grouped: bool,
+    collapsed: HashSet<String>,
     // Put the `clipboard` on global staste
     // otherwise "clipboard was dropped very quickly"
     clipboard: Option<arboard::Clipboard>,
     show_help: bool,
     show_detail: bool,
+    show_problems: bool,
+    /// Lines scrolled past in the problems popup
+    problems_scroll: u16,
+    /// Local clone to open in `$EDITOR` once the key is handled
+    edit_path: Option<PathBuf>,
+    /// Commit activity still coming in from the background, `None` once it's done
+    activity: Option<mpsc::Receiver<activity::Update>>,
 }
 
 impl App {
-    pub fn new(stats: Stats) -> Self {
-        let items = stats.repos;
+    pub fn new(
+        stats: Stats,
+        sort_by: Option<SortBy>,
+        mut columns: Vec<Column>,
+        activity: mpsc::Receiver<activity::Update>,
+    ) -> Self {
+        Column::add_present(&mut columns, &stats.repos);
+        let mut items = stats.repos;
+        if let Some(sort_by) = sort_by {
+            sort_by.sort(&mut items);
+        }
         let filtered: Vec<usize> = (0..items.len()).collect();
+        let lines = filtered.iter().map(|&i| Line::Repo(i)).collect();
         Self {
             state: TableState::default().with_selected(0),
-            sort_by: SortBy::Name,
-            scroll_state: ScrollbarState::new((items.len() - 1) * ITEM_HEIGHT),
+            sort_by: sort_by.unwrap_or(SortBy::Name),
+            columns,
+            scroll_state: ScrollbarState::new(items.len().saturating_sub(1) * ITEM_HEIGHT),
             colors: AppColor::new(),
             items,
+            problems: stats.problems,
             filtered,
             filter: None,
             filtering: false,
+            lines,
+            grouped: false,
+            collapsed: HashSet::new(),
             clipboard: arboard::Clipboard::new().ok(),
             show_help: false,
             show_detail: false,
+            show_problems: false,
+            problems_scroll: 0,
+            edit_path: None,
+            activity: Some(activity),
         }
     }
 
     fn sort(&mut self) {
-        match self.sort_by {
-            SortBy::Name => self.items.sort_by(|a, b| a.name.cmp(&b.name)),
-            SortBy::Stars => self.items.sort_by(|a, b| b.stars.cmp(&a.stars)),
-            SortBy::Forks => self.items.sort_by(|a, b| b.forks.cmp(&a.forks)),
-            SortBy::Created => self.items.sort_by(|a, b| a.created_at.cmp(&b.created_at)), // oldest first
-            SortBy::Updated => self.items.sort_by(|a, b| b.pushed_at.cmp(&a.pushed_at)), // most recent first
+        self.sort_by.sort(&mut self.items);
+        // show what the table is sorted by
+        if let Some(column) = self.sort_by.column()
+            && !self.columns.contains(&column)
+        {
+            self.columns.push(column);
         }
-        self.state.select(Some(0));
+        // indices moved, refresh them
+        self.apply_filter();
         self.scroll_state = self.scroll_state.position(0);
     }
 
     pub fn next_row(&mut self) {
+        if self.lines.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.items.len() - 1 {
+                if i >= self.lines.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -87,10 +183,13 @@ impl App {
     }
 
     pub fn previous_row(&mut self) {
+        if self.lines.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.items.len() - 1
+                    self.lines.len() - 1
                 } else {
                     i - 1
                 }
@@ -102,36 +201,132 @@ impl App {
     }
 
     fn export_markdown(&self) {
-        let mut md = String::new();
-
-        // header
-        md.push_str("| Name | Stars | Forks | License | Age | Updated |\n");
-        md.push_str("|------|-------|-------|---------|-----|---------|\n");
-
         // use filtered so what you see is what you export
-        for &idx in &self.filtered {
-            let r = &self.items[idx];
-            let row = r.ref_array();
-            md.push_str(&format!(
-                "| {} | {} | {} | {} | {} | {} |\n",
-                row[0], row[1], row[2], row[3], row[4], row[5]
-            ));
-        }
-        let _ = std::fs::write("output.md", md);
+        let repos: Vec<_> = self.filtered.iter().map(|&idx| &self.items[idx]).collect();
+        let _ = std::fs::write("output.md", output::markdown(&repos, &self.columns));
     }
 
+    /// Every word of the filter must match the name, a package, the list's description of the repo
+    /// or a keyword such as `no releases`, `source:word` matches the inputs instead.
     fn apply_filter(&mut self) {
         let query = self.filter.as_deref().unwrap_or("").to_lowercase();
         self.filtered = self
             .items
             .iter()
             .enumerate()
-            .filter(|(_, r)| r.name.to_lowercase().contains(&query))
+            .filter(|(_, r)| {
+                query
+                    .split_whitespace()
+                    .all(|word| match word.strip_prefix("source:") {
+                        Some(source) => r.source().to_lowercase().contains(source),
+                        None => {
+                            let mentions = |text: &Option<String>| {
+                                text.as_ref()
+                                    .is_some_and(|t| t.to_lowercase().contains(word))
+                            };
+                            r.id.name.to_lowercase().contains(word)
+                                || r.packages.iter().any(|p| p.to_lowercase().contains(word))
+                                || mentions(&r.label)
+                                || mentions(&r.annotation)
+                                || r.keywords().iter().any(|k| k.contains(word))
+                        }
+                    })
+            })
             .map(|(i, _)| i)
             .collect();
+        self.build_lines();
+        self.state.select(Some(0));
+    }
+
+    /// Lay out the filtered repos, under their category headers when grouped.
+    fn build_lines(&mut self) {
+        self.lines = if self.grouped {
+            // groups appear in the order of their first repo
+            let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+            for &idx in &self.filtered {
+                let category = self.items[idx].category();
+                match groups.iter_mut().find(|(c, _)| *c == category) {
+                    Some((_, repos)) => repos.push(idx),
+                    None => groups.push((category, vec![idx])),
+                }
+            }
+            let mut lines = Vec::new();
+            for (category, repos) in groups {
+                let collapsed = self.collapsed.contains(&category);
+                lines.push(Line::Group {
+                    count: repos.len(),
+                    category,
+                    collapsed,
+                });
+                if !collapsed {
+                    lines.extend(repos.into_iter().map(Line::Repo));
+                }
+            }
+            lines
+        } else {
+            self.filtered.iter().map(|&idx| Line::Repo(idx)).collect()
+        };
+        self.scroll_state = self
+            .scroll_state
+            .content_length(self.lines.len().saturating_sub(1) * ITEM_HEIGHT);
+    }
+
+    /// Show every optional column, or hide them all if some are shown.
+    fn toggle_columns(&mut self) {
+        if self.columns.iter().any(|c| Column::ALL.contains(c)) {
+            self.columns.retain(|c| !Column::ALL.contains(c));
+        } else {
+            self.columns.extend(Column::ALL);
+        }
+    }
+
+    fn toggle_grouping(&mut self) {
+        self.grouped = !self.grouped;
+        self.build_lines();
         self.state.select(Some(0));
     }
 
+    /// Collapse or expand the group under the cursor.
+    fn toggle_collapse(&mut self) {
+        let category = match self.state.selected().and_then(|i| self.lines.get(i)) {
+            Some(Line::Group { category, .. }) => category.clone(),
+            Some(Line::Repo(idx)) if self.grouped => self.items[*idx].category(),
+            _ => return,
+        };
+        if !self.collapsed.remove(&category) {
+            self.collapsed.insert(category.clone());
+        }
+        self.build_lines();
+        // keep the cursor on the toggled group
+        let i = self
+            .lines
+            .iter()
+            .position(|line| matches!(line, Line::Group { category: c, .. } if *c == category));
+        self.state.select(i.or(Some(0)));
+    }
+
+    /// Collapse every group, or expand them all if some are collapsed.
+    fn toggle_collapse_all(&mut self) {
+        if !self.grouped {
+            return;
+        }
+        if self.collapsed.is_empty() {
+            self.collapsed = self.items.iter().map(Repo::category).collect();
+        } else {
+            self.collapsed.clear();
+        }
+        self.build_lines();
+        self.state.select(Some(0));
+    }
+
+    /// The repo under the cursor, `None` on a category header.
+    fn selected_repo(&self) -> Option<usize> {
+        match self.lines.get(self.state.selected()?)? {
+            Line::Repo(idx) => Some(*idx),
+            Line::Group { .. } => None,
+        }
+    }
+
     fn popularity_color(stars: u32) -> Color {
         if stars >= 10_000 {
             tailwind::LIME.c500 // very popular
@@ -142,6 +337,14 @@ impl App {
         }
     }
 
+    fn bus_factor_color(bus_factor: u32) -> Color {
+        match bus_factor {
+            1 => tailwind::ORANGE.c600, // single maintainer
+            2 => tailwind::YELLOW.c500,
+            _ => tailwind::WHITE,
+        }
+    }
+
     fn abandoned_color(pushed_at: DateTime<Utc>) -> Color {
         let days = (Utc::now() - pushed_at).num_days();
         if days >= (365 * 2) {
@@ -153,9 +356,48 @@ impl App {
         }
     }
 
+    /// Take in whatever commit activity arrived since the last frame.
+    fn receive_activity(&mut self) {
+        let Some(rx) = &mut self.activity else {
+            return;
+        };
+        let mut received = false;
+        loop {
+            match rx.try_recv() {
+                Ok(update) => {
+                    activity::apply(&mut self.items, update);
+                    received = true;
+                }
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    self.activity = None;
+                    break;
+                }
+            }
+        }
+        // The commits order is only known once the activity is in
+        if received && matches!(self.sort_by, SortBy::Commits) {
+            self.resort();
+        }
+    }
+
+    /// Sort again after the data changed, keeping the selected repo selected.
+    fn resort(&mut self) {
+        let selected = self.selected_repo().map(|i| self.items[i].id.key());
+        self.sort_by.sort(&mut self.items);
+        self.apply_filter();
+        let line = selected.and_then(|key| {
+            self.lines
+                .iter()
+                .position(|line| matches!(line, Line::Repo(i) if self.items[*i].id.key() == key))
+        });
+        if let Some(line) = line {
+            self.state.select(Some(line));
+        }
+    }
+
     fn repo_url(&self, i: usize) -> String {
-        let item = &self.items[i];
-        format!("https://github.com/{}/{}", item.owner, item.name)
+        self.items[i].id.url()
     }
 
     pub fn run(mut self, terminal: &mut DefaultTerminal) -> Result<(), Error> {
@@ -163,10 +405,24 @@ impl App {
             .draw(|f| f.render_widget(Paragraph::new("Fetching stats...").centered(), f.area()))?;
 
         loop {
+            self.receive_activity();
             terminal.draw(|frame| self.render(frame))?;
             if self.handle_key()? {
                 return Ok(());
             }
+            if let Some(path) = self.edit_path.take() {
+                Self::edit(terminal, &path)?;
+            }
         }
     }
+
+    /// Hand the terminal over to the editor for a local clone, then take it back.
+    fn edit(terminal: &mut DefaultTerminal, path: &Path) -> Result<(), Error> {
+        let mut editor = editor::command()?;
+        ratatui::restore();
+        let _ = editor.arg(path).current_dir(path).status();
+        *terminal = ratatui::init();
+        terminal.clear()?;
+        Ok(())
+    }
 }